@@ -0,0 +1,69 @@
+//! Prime and key generation timings: `cargo bench --bench keygen`.
+//!
+//! The baseline draws a fresh random candidate and runs full Miller-Rabin on
+//! each one, as `generate_probable_prime` did before the incremental sieve.
+
+use ft_ssl::algorithms::primality::{generate_probable_prime, is_probable_prime, prime_checks_for_size};
+use ft_ssl::algorithms::rng::{CryptoRng, HmacDrbg};
+use ft_ssl::algorithms::rsa::{generate_key, generate_multi_prime_key, private_operation};
+use num::bigint::Sign;
+use num::{BigInt, One};
+use std::time::{Duration, Instant};
+
+fn baseline_probable_prime(bits: usize, rng: &mut dyn CryptoRng) -> BigInt {
+    let mut bytes = vec![0u8; bits.div_ceil(8)];
+    let rounds = prime_checks_for_size(bits);
+    loop {
+        rng.fill_bytes(&mut bytes);
+        let mut candidate = BigInt::from_bytes_be(Sign::Plus, &bytes) >> (bytes.len() * 8 - bits);
+        candidate |= BigInt::from(3) << (bits - 2);
+        candidate |= BigInt::one();
+        if is_probable_prime(&candidate, rounds, rng) {
+            return candidate;
+        }
+    }
+}
+
+/// Mean time per call over `runs` calls, from a fixed seed so that every
+/// run of the benchmark does the same work.
+fn time(runs: u32, mut generate: impl FnMut(&mut dyn CryptoRng)) -> Duration {
+    let mut rng = HmacDrbg::from_seed(b"keygen benchmark");
+    let start = Instant::now();
+    for _ in 0..runs {
+        generate(&mut rng);
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    for (bits, runs) in [(1024, 16), (2048, 4)] {
+        let baseline = time(runs, |rng| {
+            baseline_probable_prime(bits, rng);
+        });
+        let sieved = time(runs, |rng| {
+            generate_probable_prime(bits, rng);
+        });
+        println!(
+            "{:>4}-bit prime: baseline {:>10.1?}  sieved {:>10.1?}  ({:.1}x)",
+            bits,
+            baseline,
+            sieved,
+            baseline.as_secs_f64() / sieved.as_secs_f64()
+        );
+    }
+    for (bits, runs) in [(2048, 4), (4096, 1)] {
+        let keygen = time(runs, |rng| {
+            generate_key(bits, &BigInt::from(65537), rng).unwrap();
+        });
+        println!("{:>4}-bit RSA key: {:>10.1?}", bits, keygen);
+    }
+    for (bits, primes) in [(2048, 2), (2048, 3), (4096, 2), (4096, 3), (4096, 4)] {
+        let mut rng = HmacDrbg::from_seed(b"keygen benchmark");
+        let key = generate_multi_prime_key(bits, primes, &BigInt::from(65537), &mut rng).unwrap();
+        let input = vec![0x5a; bits / 8 - 1];
+        let private = time(64, |_| {
+            private_operation(&key, &input).unwrap();
+        });
+        println!("{:>4}-bit {}-prime private operation: {:>10.1?}", bits, primes, private);
+    }
+}
@@ -0,0 +1,219 @@
+use super::cipher::BlockCipher;
+
+// FIPS 197. State bytes are kept in the standard's column-major order, so a
+// 16-byte block maps onto the state without any transposition.
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+fn inverse_sbox() -> [u8; 256] {
+    let mut inverse = [0u8; 256];
+    for (i, &value) in SBOX.iter().enumerate() {
+        inverse[value as usize] = i as u8;
+    }
+    inverse
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1.
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// AES with a 128, 192 or 256-bit key, chosen by the key length.
+#[derive(Clone)]
+pub struct Aes {
+    round_keys: Vec<[u8; 16]>,
+    inverse_sbox: [u8; 256],
+}
+
+impl Aes {
+    pub fn new(key: &[u8]) -> Self {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            len => panic!("AES key must be 16, 24 or 32 bytes, got {}", len),
+        };
+        let rounds = nk + 6;
+        let mut words: Vec<[u8; 4]> = key.chunks(4).map(|w| w.try_into().unwrap()).collect();
+
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|b| SBOX[b as usize]);
+                temp[0] ^= RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|b| SBOX[b as usize]);
+            }
+            let previous = words[i - nk];
+            words.push([
+                previous[0] ^ temp[0],
+                previous[1] ^ temp[1],
+                previous[2] ^ temp[2],
+                previous[3] ^ temp[3],
+            ]);
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|round| {
+                let mut key = [0u8; 16];
+                for (dst, word) in key.chunks_mut(4).zip(round) {
+                    dst.copy_from_slice(word);
+                }
+                key
+            })
+            .collect();
+        Aes {
+            round_keys,
+            inverse_sbox: inverse_sbox(),
+        }
+    }
+
+    fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+}
+
+fn add_round_key(state: &mut [u8], key: &[u8; 16]) {
+    for (s, k) in state.iter_mut().zip(key.iter()) {
+        *s ^= k;
+    }
+}
+
+fn shift_rows(state: &mut [u8]) {
+    let copy: [u8; 16] = (&*state).try_into().unwrap();
+    for column in 0..4 {
+        for row in 0..4 {
+            state[4 * column + row] = copy[4 * ((column + row) % 4) + row];
+        }
+    }
+}
+
+fn inverse_shift_rows(state: &mut [u8]) {
+    let copy: [u8; 16] = (&*state).try_into().unwrap();
+    for column in 0..4 {
+        for row in 0..4 {
+            state[4 * ((column + row) % 4) + row] = copy[4 * column + row];
+        }
+    }
+}
+
+fn mix_columns(state: &mut [u8], matrix: [u8; 4]) {
+    for column in state.chunks_mut(4) {
+        let c: [u8; 4] = (&*column).try_into().unwrap();
+        for row in 0..4 {
+            column[row] = gmul(c[0], matrix[(4 - row) % 4])
+                ^ gmul(c[1], matrix[(5 - row) % 4])
+                ^ gmul(c[2], matrix[(6 - row) % 4])
+                ^ gmul(c[3], matrix[(7 - row) % 4]);
+        }
+    }
+}
+
+impl BlockCipher for Aes {
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let rounds = self.rounds();
+        add_round_key(block, &self.round_keys[0]);
+
+        for round in 1..=rounds {
+            for b in block.iter_mut() {
+                *b = SBOX[*b as usize];
+            }
+            shift_rows(block);
+            if round != rounds {
+                mix_columns(block, [2, 3, 1, 1]);
+            }
+            add_round_key(block, &self.round_keys[round]);
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let rounds = self.rounds();
+        add_round_key(block, &self.round_keys[rounds]);
+
+        for round in (0..rounds).rev() {
+            inverse_shift_rows(block);
+            for b in block.iter_mut() {
+                *b = self.inverse_sbox[*b as usize];
+            }
+            add_round_key(block, &self.round_keys[round]);
+            if round != 0 {
+                mix_columns(block, [14, 11, 13, 9]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_aes_fips197_appendix_c() {
+        let plaintext = unhex("00112233445566778899aabbccddeeff");
+        let cases = [
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ];
+
+        for (key, expected) in cases {
+            let aes = Aes::new(&unhex(key));
+            let mut block = plaintext.clone();
+            aes.encrypt_block(&mut block);
+            assert_eq!(block, unhex(expected));
+            aes.decrypt_block(&mut block);
+            assert_eq!(block, plaintext);
+        }
+    }
+}
@@ -0,0 +1,401 @@
+use super::aes::Aes;
+use super::des::{Des, TripleDes};
+
+/// A keyed block cipher operating in place on a single block.
+pub trait BlockCipher {
+    fn block_size(&self) -> usize;
+    fn encrypt_block(&self, block: &mut [u8]);
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+/// Symmetric ciphers known by their OpenSSL names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+    DesEcb,
+    DesCbc,
+    DesEde,
+    DesEdeCbc,
+    DesEde3,
+    DesEde3Cbc,
+    Aes128Ecb,
+    Aes128Cbc,
+    Aes128Ctr,
+    Aes192Ecb,
+    Aes192Cbc,
+    Aes192Ctr,
+    Aes256Ecb,
+    Aes256Cbc,
+    Aes256Ctr,
+}
+
+impl Cipher {
+    /// Every name `from_name` accepts, aliases included.
+    pub const NAMES: &'static [&'static str] = &[
+        "des-ecb", "des", "des-cbc", "des-ede", "des-ede-ecb", "des-ede-cbc", "des-ede3",
+        "des-ede3-ecb", "des3", "des-ede3-cbc", "aes-128-ecb", "aes128", "aes-128-cbc",
+        "aes-128-ctr", "aes-192-ecb", "aes192", "aes-192-cbc", "aes-192-ctr", "aes-256-ecb",
+        "aes256", "aes-256-cbc", "aes-256-ctr",
+    ];
+
+    /// Looks a cipher up by name, accepting the same aliases as OpenSSL
+    /// (`des` for `des-cbc`, `des3` for `des-ede3-cbc`, `aes256` for
+    /// `aes-256-cbc`).
+    pub fn from_name(name: &str) -> Option<Cipher> {
+        match name.to_ascii_lowercase().as_str() {
+            "des-ecb" => Some(Cipher::DesEcb),
+            "des" | "des-cbc" => Some(Cipher::DesCbc),
+            "des-ede" | "des-ede-ecb" => Some(Cipher::DesEde),
+            "des-ede-cbc" => Some(Cipher::DesEdeCbc),
+            "des-ede3" | "des-ede3-ecb" => Some(Cipher::DesEde3),
+            "des3" | "des-ede3-cbc" => Some(Cipher::DesEde3Cbc),
+            "aes-128-ecb" => Some(Cipher::Aes128Ecb),
+            "aes128" | "aes-128-cbc" => Some(Cipher::Aes128Cbc),
+            "aes-128-ctr" => Some(Cipher::Aes128Ctr),
+            "aes-192-ecb" => Some(Cipher::Aes192Ecb),
+            "aes192" | "aes-192-cbc" => Some(Cipher::Aes192Cbc),
+            "aes-192-ctr" => Some(Cipher::Aes192Ctr),
+            "aes-256-ecb" => Some(Cipher::Aes256Ecb),
+            "aes256" | "aes-256-cbc" => Some(Cipher::Aes256Cbc),
+            "aes-256-ctr" => Some(Cipher::Aes256Ctr),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cipher::DesEcb => "des-ecb",
+            Cipher::DesCbc => "des-cbc",
+            Cipher::DesEde => "des-ede",
+            Cipher::DesEdeCbc => "des-ede-cbc",
+            Cipher::DesEde3 => "des-ede3",
+            Cipher::DesEde3Cbc => "des-ede3-cbc",
+            Cipher::Aes128Ecb => "aes-128-ecb",
+            Cipher::Aes128Cbc => "aes-128-cbc",
+            Cipher::Aes128Ctr => "aes-128-ctr",
+            Cipher::Aes192Ecb => "aes-192-ecb",
+            Cipher::Aes192Cbc => "aes-192-cbc",
+            Cipher::Aes192Ctr => "aes-192-ctr",
+            Cipher::Aes256Ecb => "aes-256-ecb",
+            Cipher::Aes256Cbc => "aes-256-cbc",
+            Cipher::Aes256Ctr => "aes-256-ctr",
+        }
+    }
+
+    pub fn key_len(&self) -> usize {
+        match self {
+            Cipher::DesEcb | Cipher::DesCbc => 8,
+            Cipher::DesEde | Cipher::DesEdeCbc => 16,
+            Cipher::DesEde3 | Cipher::DesEde3Cbc => 24,
+            Cipher::Aes128Ecb | Cipher::Aes128Cbc | Cipher::Aes128Ctr => 16,
+            Cipher::Aes192Ecb | Cipher::Aes192Cbc | Cipher::Aes192Ctr => 24,
+            Cipher::Aes256Ecb | Cipher::Aes256Cbc | Cipher::Aes256Ctr => 32,
+        }
+    }
+
+    /// Length of the IV, zero for ECB modes.
+    pub fn iv_len(&self) -> usize {
+        match self {
+            Cipher::DesEcb | Cipher::DesEde | Cipher::DesEde3 => 0,
+            Cipher::DesCbc | Cipher::DesEdeCbc | Cipher::DesEde3Cbc => 8,
+            Cipher::Aes128Ecb | Cipher::Aes192Ecb | Cipher::Aes256Ecb => 0,
+            _ => 16,
+        }
+    }
+
+    pub fn is_cbc(&self) -> bool {
+        !self.is_ctr() && self.iv_len() > 0
+    }
+
+    fn is_ctr(&self) -> bool {
+        matches!(
+            self,
+            Cipher::Aes128Ctr | Cipher::Aes192Ctr | Cipher::Aes256Ctr
+        )
+    }
+
+    fn block_cipher(&self, key: &[u8]) -> Box<dyn BlockCipher> {
+        match self {
+            Cipher::DesEcb | Cipher::DesCbc => Box::new(Des::new(key)),
+            Cipher::DesEde | Cipher::DesEdeCbc | Cipher::DesEde3 | Cipher::DesEde3Cbc => {
+                Box::new(TripleDes::new(key))
+            }
+            _ => Box::new(Aes::new(key)),
+        }
+    }
+
+    /// Encrypts `data`, with PKCS#7 padding for the block modes.
+    pub fn encrypt(&self, key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+        let cipher = self.block_cipher(key);
+        if self.is_ctr() {
+            ctr_apply(cipher.as_ref(), iv, data)
+        } else if self.iv_len() == 0 {
+            ecb_encrypt(cipher.as_ref(), data)
+        } else {
+            cbc_encrypt(cipher.as_ref(), iv, data)
+        }
+    }
+
+    /// Decrypts `data` and strips its PKCS#7 padding, returning `None` when
+    /// the padding is malformed (typically a wrong key). CTR mode has no
+    /// padding and always succeeds.
+    pub fn decrypt(&self, key: &[u8], iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+        let cipher = self.block_cipher(key);
+        if self.is_ctr() {
+            Some(ctr_apply(cipher.as_ref(), iv, data))
+        } else if self.iv_len() == 0 {
+            ecb_decrypt(cipher.as_ref(), data)
+        } else {
+            cbc_decrypt(cipher.as_ref(), iv, data)
+        }
+    }
+}
+
+pub fn pkcs7_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    let pad = block_size - data.len() % block_size;
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + pad, pad as u8);
+    padded
+}
+
+pub fn pkcs7_unpad(mut data: Vec<u8>, block_size: usize) -> Option<Vec<u8>> {
+    let pad = *data.last()? as usize;
+    if pad == 0 || pad > block_size || pad > data.len() {
+        return None;
+    }
+    if data[data.len() - pad..].iter().any(|&b| b as usize != pad) {
+        return None;
+    }
+    data.truncate(data.len() - pad);
+    Some(data)
+}
+
+pub fn ecb_encrypt(cipher: &dyn BlockCipher, data: &[u8]) -> Vec<u8> {
+    let size = cipher.block_size();
+    let mut output = pkcs7_pad(data, size);
+
+    for block in output.chunks_mut(size) {
+        cipher.encrypt_block(block);
+    }
+    output
+}
+
+pub fn ecb_decrypt(cipher: &dyn BlockCipher, data: &[u8]) -> Option<Vec<u8>> {
+    let size = cipher.block_size();
+    if data.is_empty() || !data.len().is_multiple_of(size) {
+        return None;
+    }
+    let mut output = data.to_vec();
+
+    for block in output.chunks_mut(size) {
+        cipher.decrypt_block(block);
+    }
+    pkcs7_unpad(output, size)
+}
+
+pub fn cbc_encrypt(cipher: &dyn BlockCipher, iv: &[u8], data: &[u8]) -> Vec<u8> {
+    let size = cipher.block_size();
+    let mut output = pkcs7_pad(data, size);
+    let mut previous = iv.to_vec();
+
+    for block in output.chunks_mut(size) {
+        for (b, p) in block.iter_mut().zip(previous.iter()) {
+            *b ^= p;
+        }
+        cipher.encrypt_block(block);
+        previous.copy_from_slice(block);
+    }
+    output
+}
+
+pub fn cbc_decrypt(cipher: &dyn BlockCipher, iv: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let size = cipher.block_size();
+    if data.is_empty() || !data.len().is_multiple_of(size) {
+        return None;
+    }
+    let mut output = data.to_vec();
+    let mut previous = iv.to_vec();
+
+    for block in output.chunks_mut(size) {
+        let current = block.to_vec();
+        cipher.decrypt_block(block);
+        for (b, p) in block.iter_mut().zip(previous.iter()) {
+            *b ^= p;
+        }
+        previous = current;
+    }
+    pkcs7_unpad(output, size)
+}
+
+/// CTR mode with a big-endian counter spanning the whole block, as in
+/// SP 800-38A. Encryption and decryption are the same operation.
+pub fn ctr_apply(cipher: &dyn BlockCipher, iv: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut counter = iv.to_vec();
+    let mut output = data.to_vec();
+
+    for chunk in output.chunks_mut(cipher.block_size()) {
+        let mut keystream = counter.clone();
+        cipher.encrypt_block(&mut keystream);
+        for (b, k) in chunk.iter_mut().zip(keystream.iter()) {
+            *b ^= k;
+        }
+        for byte in counter.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_name_resolves() {
+        for name in Cipher::NAMES {
+            assert!(Cipher::from_name(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_des_ede3_cbc_matches_openssl() {
+        // printf '0123456789abcdef' | openssl enc -des-ede3-cbc \
+        //     -K 0123456789abcdef23456789abcdef01456789abcdef0123 -iv 0000000000000000
+        let key = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd,
+            0xef, 0x01, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23,
+        ];
+        let expected = [
+            0xf0, 0x4c, 0x6c, 0x80, 0x6a, 0x23, 0xe5, 0x9f, 0x34, 0x11, 0x9a, 0xb5, 0x94, 0x73,
+            0x5f, 0x40, 0x2b, 0x65, 0x16, 0xf1, 0xc5, 0x0e, 0x4c, 0xbc,
+        ];
+        let cipher = Cipher::from_name("des3").unwrap();
+        let encrypted = cipher.encrypt(&key, &[0; 8], b"0123456789abcdef");
+
+        assert_eq!(encrypted, expected);
+        assert_eq!(
+            cipher.decrypt(&key, &[0; 8], &encrypted).unwrap(),
+            b"0123456789abcdef"
+        );
+    }
+
+    #[test]
+    fn test_des_ecb_matches_openssl() {
+        // printf '0123456789abcdef' | openssl enc -des-ecb -K 133457799BBCDFF1 -nopad
+        let key = 0x133457799BBCDFF1u64.to_be_bytes();
+        let encrypted = Cipher::DesEcb.encrypt(&key, &[], b"0123456789abcdef");
+
+        assert_eq!(
+            &encrypted[..16],
+            &[
+                0x6c, 0xbd, 0x22, 0x85, 0x8b, 0xce, 0xdb, 0x79, 0xe8, 0x24, 0x45, 0x8b, 0xc7, 0xdf,
+                0xca, 0x39
+            ]
+        );
+        assert_eq!(encrypted.len(), 24);
+    }
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const SP800_38A_PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a\
+        ae2d8a571e03ac9c9eb76fac45af8e51\
+        30c81c46a35ce411e5fbc1191a0a52ef\
+        f69f2445df4f9b17ad2b417be66c3710";
+
+    #[test]
+    fn test_aes_cbc_sp800_38a() {
+        // F.2.1, F.2.3 and F.2.5; the trailing block is the PKCS#7 padding.
+        let iv = unhex("000102030405060708090a0b0c0d0e0f");
+        let plaintext = unhex(SP800_38A_PLAINTEXT);
+        let cases = [
+            (
+                Cipher::Aes128Cbc,
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2\
+                 73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+            ),
+            (
+                Cipher::Aes192Cbc,
+                "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+                "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a\
+                 571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd",
+            ),
+            (
+                Cipher::Aes256Cbc,
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+                 39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+            ),
+        ];
+
+        for (cipher, key, expected) in cases {
+            let key = unhex(key);
+            let expected: String = expected.split_whitespace().collect();
+            let encrypted = cipher.encrypt(&key, &iv, &plaintext);
+
+            assert_eq!(&encrypted[..64], &unhex(&expected)[..]);
+            assert_eq!(cipher.decrypt(&key, &iv, &encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_aes_ctr_sp800_38a() {
+        // F.5.1, F.5.3 and F.5.5.
+        let counter = unhex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = unhex(SP800_38A_PLAINTEXT);
+        let cases = [
+            (
+                Cipher::Aes128Ctr,
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+                 5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+            ),
+            (
+                Cipher::Aes192Ctr,
+                "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+                "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e94\
+                 1e36b26bd1ebc670d1bd1d665620abf74f78a7f6d29809585a97daec58c6b050",
+            ),
+            (
+                Cipher::Aes256Ctr,
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5\
+                 2b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6",
+            ),
+        ];
+
+        for (cipher, key, expected) in cases {
+            let key = unhex(key);
+            let expected: String = expected.split_whitespace().collect();
+            let encrypted = cipher.encrypt(&key, &counter, &plaintext);
+
+            assert_eq!(encrypted, unhex(&expected));
+            assert_eq!(cipher.decrypt(&key, &counter, &encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_two_key_triple_des_cbc_matches_openssl() {
+        // printf 'two key triple des' | openssl enc -des-ede-cbc \
+        //     -K 30313233343536373839616263646566 -iv 696e697476656374
+        let key = b"0123456789abcdef";
+        let expected = [
+            0xfe, 0x1e, 0x7b, 0xae, 0xa2, 0x19, 0x46, 0xff, 0x21, 0x4b, 0xde, 0x11, 0x04, 0xb1,
+            0x65, 0xaf, 0x6b, 0xed, 0xce, 0x06, 0xf3, 0x86, 0xd1, 0x97,
+        ];
+        let encrypted = Cipher::DesEdeCbc.encrypt(key, b"initvect", b"two key triple des");
+
+        assert_eq!(encrypted, expected);
+        assert_eq!(
+            Cipher::DesEdeCbc.decrypt(key, b"initvect", &encrypted).unwrap(),
+            b"two key triple des"
+        );
+    }
+}
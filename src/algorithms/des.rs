@@ -0,0 +1,261 @@
+use super::cipher::BlockCipher;
+
+// All tables are taken from FIPS 46-3. Bit positions are 1-based and counted
+// from the most significant bit, exactly as printed in the standard.
+
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, 62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8, 57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, 61,
+    53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, 38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29, 36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27, 34,
+    2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, 8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, 16, 17, 18,
+    19, 20, 21, 20, 21, 22, 23, 24, 25, 24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, 2, 8, 24, 14, 32, 27, 3, 9, 19,
+    13, 30, 6, 22, 11, 4, 25,
+];
+
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, 10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60,
+    52, 44, 36, 63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, 14, 6, 61, 53, 45, 37, 29,
+    21, 13, 5, 28, 20, 12, 4,
+];
+
+const PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, 41, 52,
+    31, 37, 47, 55, 30, 40, 51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+const SBOXES: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, 0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12,
+        11, 9, 5, 3, 8, 4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, 15, 12, 8, 2, 4, 9,
+        1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, 3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1,
+        10, 6, 9, 11, 5, 0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, 13, 8, 10, 1, 3, 15,
+        4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, 13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5,
+        14, 12, 11, 15, 1, 13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, 1, 10, 13, 0, 6,
+        9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, 13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2,
+        12, 1, 10, 14, 9, 10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, 3, 15, 0, 6, 10, 1,
+        13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, 14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15,
+        10, 3, 9, 8, 6, 4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, 11, 8, 12, 7, 1, 14,
+        2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, 10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13,
+        14, 0, 11, 3, 8, 9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, 4, 3, 2, 12, 9, 5,
+        15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, 13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5,
+        12, 2, 15, 8, 6, 1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, 6, 11, 13, 8, 1, 4,
+        10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, 1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6,
+        11, 0, 14, 9, 2, 7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, 2, 1, 14, 7, 4, 10,
+        8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+/// Picks bits out of `input` (which is `width` bits wide) in the order given
+/// by `table`, producing a `table.len()`-bit value.
+fn permute(input: u64, width: u32, table: &[u8]) -> u64 {
+    let mut output = 0u64;
+
+    for &position in table {
+        let bit = (input >> (width - position as u32)) & 1;
+        output = (output << 1) | bit;
+    }
+    output
+}
+
+fn rotate_28(half: u64, amount: u32) -> u64 {
+    ((half << amount) | (half >> (28 - amount))) & 0x0FFF_FFFF
+}
+
+fn feistel(right: u64, subkey: u64) -> u64 {
+    let expanded = permute(right, 32, &E) ^ subkey;
+    let mut output = 0u64;
+
+    for (i, sbox) in SBOXES.iter().enumerate() {
+        let chunk = (expanded >> (42 - 6 * i)) & 0x3F;
+        let row = ((chunk & 0x20) >> 4) | (chunk & 0x01);
+        let column = (chunk >> 1) & 0x0F;
+        output = (output << 4) | sbox[(row * 16 + column) as usize] as u64;
+    }
+    permute(output, 32, &P)
+}
+
+/// Single DES with a precomputed key schedule.
+#[derive(Clone)]
+pub struct Des {
+    subkeys: [u64; 16],
+}
+
+impl Des {
+    pub fn new(key: &[u8]) -> Self {
+        assert!(key.len() == 8, "DES key must be 8 bytes, got {}", key.len());
+        let key = u64::from_be_bytes(key.try_into().unwrap());
+        let permuted = permute(key, 64, &PC1);
+        let mut c = permuted >> 28;
+        let mut d = permuted & 0x0FFF_FFFF;
+        let mut subkeys = [0u64; 16];
+
+        for (subkey, &shift) in subkeys.iter_mut().zip(SHIFTS.iter()) {
+            c = rotate_28(c, shift);
+            d = rotate_28(d, shift);
+            *subkey = permute((c << 28) | d, 56, &PC2);
+        }
+        Des { subkeys }
+    }
+
+    fn crypt(&self, block: u64, decrypt: bool) -> u64 {
+        let permuted = permute(block, 64, &IP);
+        let mut left = permuted >> 32;
+        let mut right = permuted & 0xFFFF_FFFF;
+
+        for round in 0..16 {
+            let subkey = if decrypt {
+                self.subkeys[15 - round]
+            } else {
+                self.subkeys[round]
+            };
+            let next = left ^ feistel(right, subkey);
+            left = right;
+            right = next;
+        }
+        permute((right << 32) | left, 64, &FP)
+    }
+
+    pub fn encrypt_u64(&self, block: u64) -> u64 {
+        self.crypt(block, false)
+    }
+
+    pub fn decrypt_u64(&self, block: u64) -> u64 {
+        self.crypt(block, true)
+    }
+}
+
+impl BlockCipher for Des {
+    fn block_size(&self) -> usize {
+        8
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let value = self.encrypt_u64(u64::from_be_bytes((&*block).try_into().unwrap()));
+        block.copy_from_slice(&value.to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let value = self.decrypt_u64(u64::from_be_bytes((&*block).try_into().unwrap()));
+        block.copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Triple DES in encrypt-decrypt-encrypt order.
+///
+/// A 16-byte key selects the two-key variant (K3 = K1, OpenSSL's `des-ede`),
+/// a 24-byte key the three-key variant (`des-ede3`).
+#[derive(Clone)]
+pub struct TripleDes {
+    keys: [Des; 3],
+}
+
+impl TripleDes {
+    pub fn new(key: &[u8]) -> Self {
+        let (k1, k2, k3) = match key.len() {
+            16 => (&key[0..8], &key[8..16], &key[0..8]),
+            24 => (&key[0..8], &key[8..16], &key[16..24]),
+            len => panic!("Triple DES key must be 16 or 24 bytes, got {}", len),
+        };
+        TripleDes {
+            keys: [Des::new(k1), Des::new(k2), Des::new(k3)],
+        }
+    }
+}
+
+impl BlockCipher for TripleDes {
+    fn block_size(&self) -> usize {
+        8
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut value = u64::from_be_bytes((&*block).try_into().unwrap());
+        value = self.keys[0].encrypt_u64(value);
+        value = self.keys[1].decrypt_u64(value);
+        value = self.keys[2].encrypt_u64(value);
+        block.copy_from_slice(&value.to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut value = u64::from_be_bytes((&*block).try_into().unwrap());
+        value = self.keys[2].decrypt_u64(value);
+        value = self.keys[1].encrypt_u64(value);
+        value = self.keys[0].decrypt_u64(value);
+        block.copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_des_known_answer() {
+        // Classic worked example from "The DES Algorithm Illustrated".
+        let des = Des::new(&0x133457799BBCDFF1u64.to_be_bytes());
+        assert_eq!(des.encrypt_u64(0x0123456789ABCDEF), 0x85E813540F0AB405);
+        assert_eq!(des.decrypt_u64(0x85E813540F0AB405), 0x0123456789ABCDEF);
+    }
+
+    #[test]
+    fn test_triple_des_with_equal_keys_is_des() {
+        let key = 0x133457799BBCDFF1u64.to_be_bytes();
+        let des = Des::new(&key);
+        let tdes = TripleDes::new(&[key, key, key].concat());
+        let mut block = 0x0123456789ABCDEFu64.to_be_bytes();
+
+        tdes.encrypt_block(&mut block);
+        assert_eq!(u64::from_be_bytes(block), des.encrypt_u64(0x0123456789ABCDEF));
+    }
+
+    #[test]
+    fn test_triple_des_ede3() {
+        // openssl enc -des-ede3 -nopad -K 0123456789abcdef23456789abcdef01456789abcdef0123
+        let key = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd,
+            0xef, 0x01, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23,
+        ];
+        let tdes = TripleDes::new(&key);
+        let mut block = *b"01234567";
+
+        tdes.encrypt_block(&mut block);
+        assert_eq!(block, [0xf0, 0x4c, 0x6c, 0x80, 0x6a, 0x23, 0xe5, 0x9f]);
+        tdes.decrypt_block(&mut block);
+        assert_eq!(&block, b"01234567");
+    }
+}
@@ -0,0 +1,51 @@
+use super::md5::md5;
+use super::sha1::sha1;
+use super::sha256::sha256;
+
+/// Message digests known by their OpenSSL names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Digest {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl Digest {
+    pub fn from_name(name: &str) -> Option<Digest> {
+        match name.to_ascii_lowercase().as_str() {
+            "md5" => Some(Digest::Md5),
+            "sha1" | "sha-1" => Some(Digest::Sha1),
+            "sha256" | "sha-256" => Some(Digest::Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Digest::Md5 => "md5",
+            Digest::Sha1 => "sha1",
+            Digest::Sha256 => "sha256",
+        }
+    }
+
+    pub fn output_len(&self) -> usize {
+        match self {
+            Digest::Md5 => 16,
+            Digest::Sha1 => 20,
+            Digest::Sha256 => 32,
+        }
+    }
+
+    /// Input block size in bytes, as needed by HMAC.
+    pub fn block_size(&self) -> usize {
+        64
+    }
+
+    pub fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Digest::Md5 => md5(data).to_vec(),
+            Digest::Sha1 => sha1(data).to_vec(),
+            Digest::Sha256 => sha256(data).to_vec(),
+        }
+    }
+}
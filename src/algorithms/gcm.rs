@@ -0,0 +1,200 @@
+use super::aes::Aes;
+use super::cipher::BlockCipher;
+
+// Galois/Counter Mode over AES, NIST SP 800-38D.
+
+pub const TAG_LEN: usize = 16;
+
+/// Multiplication in GF(2^128) with GCM's reflected bit order.
+fn gf_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let mut z = 0u128;
+    let mut v = y;
+
+    for i in 0..128 {
+        if (x >> (127 - i)) & 1 == 1 {
+            z ^= v;
+        }
+        v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+    }
+    z
+}
+
+fn ghash(h: u128, aad: &[u8], ciphertext: &[u8]) -> u128 {
+    let mut y = 0u128;
+
+    for data in [aad, ciphertext] {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            y = gf_mul(y ^ u128::from_be_bytes(block), h);
+        }
+    }
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    gf_mul(y ^ lengths, h)
+}
+
+fn increment_32(counter: u128) -> u128 {
+    let low = (counter as u32).wrapping_add(1);
+    (counter & !0xffff_ffffu128) | low as u128
+}
+
+fn gctr(aes: &Aes, initial: u128, data: &[u8]) -> Vec<u8> {
+    let mut counter = initial;
+    let mut output = data.to_vec();
+
+    for chunk in output.chunks_mut(16) {
+        let mut keystream = counter.to_be_bytes();
+        aes.encrypt_block(&mut keystream);
+        for (b, k) in chunk.iter_mut().zip(keystream.iter()) {
+            *b ^= k;
+        }
+        counter = increment_32(counter);
+    }
+    output
+}
+
+/// Returns the hash subkey H and the pre-counter block J0.
+fn setup(aes: &Aes, iv: &[u8]) -> (u128, u128) {
+    let mut zero = [0u8; 16];
+    aes.encrypt_block(&mut zero);
+    let h = u128::from_be_bytes(zero);
+
+    let j0 = if iv.len() == 12 {
+        let mut block = [0u8; 16];
+        block[..12].copy_from_slice(iv);
+        block[15] = 1;
+        u128::from_be_bytes(block)
+    } else {
+        ghash(h, &[], iv)
+    };
+    (h, j0)
+}
+
+/// Encrypts `plaintext` and authenticates it together with `aad`, returning
+/// the ciphertext and the 16-byte tag.
+pub fn gcm_encrypt(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> (Vec<u8>, [u8; TAG_LEN]) {
+    let aes = Aes::new(key);
+    let (h, j0) = setup(&aes, iv);
+    let ciphertext = gctr(&aes, increment_32(j0), plaintext);
+    let s = ghash(h, aad, &ciphertext);
+    let tag = gctr(&aes, j0, &s.to_be_bytes());
+
+    (ciphertext, tag.try_into().unwrap())
+}
+
+/// Verifies `tag` and decrypts `ciphertext`, returning `None` when
+/// authentication fails.
+pub fn gcm_decrypt(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    let aes = Aes::new(key);
+    let (h, j0) = setup(&aes, iv);
+    let s = ghash(h, aad, ciphertext);
+    let expected = gctr(&aes, j0, &s.to_be_bytes());
+
+    // Compare without an early exit so timing does not leak the mismatch.
+    let difference = expected
+        .iter()
+        .zip(tag.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if tag.len() != TAG_LEN || difference != 0 {
+        return None;
+    }
+    Some(gctr(&aes, increment_32(j0), ciphertext))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        let hex: String = hex.split_whitespace().collect();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const PLAINTEXT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+        1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+
+    #[test]
+    fn test_gcm_test_case_2() {
+        let (ciphertext, tag) = gcm_encrypt(&[0; 16], &[0; 12], &[], &[0; 16]);
+
+        assert_eq!(ciphertext, unhex("0388dace60b6a392f328c2b971b2fe78"));
+        assert_eq!(tag.to_vec(), unhex("ab6e47d42cec13bdf53a67b21257bddf"));
+    }
+
+    #[test]
+    fn test_gcm_test_case_4() {
+        let key = unhex("feffe9928665731c6d6a8f9467308308");
+        let iv = unhex("cafebabefacedbaddecaf888");
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let (ciphertext, tag) = gcm_encrypt(&key, &iv, &aad, &unhex(PLAINTEXT));
+
+        assert_eq!(
+            ciphertext,
+            unhex(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091"
+            )
+        );
+        assert_eq!(tag.to_vec(), unhex("5bc94fbc3221a5db94fae95ae7121a47"));
+        assert_eq!(
+            gcm_decrypt(&key, &iv, &aad, &ciphertext, &tag).unwrap(),
+            unhex(PLAINTEXT)
+        );
+    }
+
+    #[test]
+    fn test_gcm_test_case_16() {
+        let key = unhex("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308");
+        let iv = unhex("cafebabefacedbaddecaf888");
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let (ciphertext, tag) = gcm_encrypt(&key, &iv, &aad, &unhex(PLAINTEXT));
+
+        assert_eq!(
+            ciphertext,
+            unhex(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+                 8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+            )
+        );
+        assert_eq!(tag.to_vec(), unhex("76fc6ece0f4e1768cddf8853bb2d551b"));
+    }
+
+    #[test]
+    fn test_gcm_non_96_bit_iv() {
+        // Test case 6: a 60-byte IV goes through GHASH to form J0.
+        let key = unhex("feffe9928665731c6d6a8f9467308308");
+        let iv = unhex(
+            "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728\
+             c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+        );
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let (_, tag) = gcm_encrypt(&key, &iv, &aad, &unhex(PLAINTEXT));
+
+        assert_eq!(tag.to_vec(), unhex("619cc5aefffe0bfa462af43c1699d050"));
+    }
+
+    #[test]
+    fn test_gcm_rejects_tampering() {
+        let key = [7u8; 32];
+        let (mut ciphertext, tag) = gcm_encrypt(&key, &[1; 12], b"header", b"secret payload");
+
+        assert!(gcm_decrypt(&key, &[1; 12], b"other", &ciphertext, &tag).is_none());
+        ciphertext[0] ^= 1;
+        assert!(gcm_decrypt(&key, &[1; 12], b"header", &ciphertext, &tag).is_none());
+    }
+}
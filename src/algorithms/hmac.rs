@@ -0,0 +1,63 @@
+use super::digest::Digest;
+
+// RFC 2104.
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub fn hmac(digest: Digest, key: &[u8], data: &[u8]) -> Vec<u8> {
+    let block_size = digest.block_size();
+    let mut block_key = if key.len() > block_size {
+        digest.hash(key)
+    } else {
+        key.to_vec()
+    };
+    block_key.resize(block_size, 0);
+
+    let mut inner: Vec<u8> = block_key.iter().map(|b| b ^ IPAD).collect();
+    inner.extend_from_slice(data);
+    let mut outer: Vec<u8> = block_key.iter().map(|b| b ^ OPAD).collect();
+    outer.extend_from_slice(&digest.hash(&inner));
+    digest.hash(&outer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_rfc4231_case_2() {
+        assert_eq!(
+            hex(&hmac(Digest::Sha256, b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_rfc4231_case_6_long_key() {
+        assert_eq!(
+            hex(&hmac(
+                Digest::Sha256,
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_rfc2202_sha1_and_md5() {
+        assert_eq!(
+            hex(&hmac(Digest::Sha1, b"Jefe", b"what do ya want for nothing?")),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(
+            hex(&hmac(Digest::Md5, b"Jefe", b"what do ya want for nothing?")),
+            "750c783e6ab0b503eaa86e310a5db738"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use super::digest::Digest;
+use super::hmac::hmac;
+
+/// OpenSSL's EVP_BytesToKey with an iteration count of one, as used by
+/// `openssl enc` without `-pbkdf2` and by traditional encrypted PEM keys.
+///
+/// Returns `key_len` bytes of key followed by `iv_len` bytes of IV.
+pub fn evp_bytes_to_key(
+    digest: Digest,
+    password: &[u8],
+    salt: Option<&[u8]>,
+    key_len: usize,
+    iv_len: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    let mut material: Vec<u8> = Vec::with_capacity(key_len + iv_len);
+    let mut previous: Vec<u8> = Vec::new();
+
+    while material.len() < key_len + iv_len {
+        let mut input = previous.clone();
+        input.extend_from_slice(password);
+        if let Some(salt) = salt {
+            input.extend_from_slice(salt);
+        }
+        previous = digest.hash(&input);
+        material.extend_from_slice(&previous);
+    }
+
+    let iv = material[key_len..key_len + iv_len].to_vec();
+    material.truncate(key_len);
+    (material, iv)
+}
+
+/// PKCS#5 PBKDF1 (RFC 8018 section 5.1), used by the PBES1 schemes.
+pub fn pbkdf1(digest: Digest, password: &[u8], salt: &[u8], iterations: u64, key_len: usize) -> Vec<u8> {
+    let mut input = password.to_vec();
+    input.extend_from_slice(salt);
+    let mut derived = digest.hash(&input);
+    for _ in 1..iterations {
+        derived = digest.hash(&derived);
+    }
+    derived.truncate(key_len);
+    derived
+}
+
+/// PKCS#5 PBKDF2 (RFC 8018 section 5.2) with HMAC over `digest` as PRF.
+pub fn pbkdf2(digest: Digest, password: &[u8], salt: &[u8], iterations: u64, key_len: usize) -> Vec<u8> {
+    let mut derived = Vec::with_capacity(key_len);
+    let mut index: u32 = 1;
+
+    while derived.len() < key_len {
+        let mut input = salt.to_vec();
+        input.extend_from_slice(&index.to_be_bytes());
+        let mut u = hmac(digest, password, &input);
+        let mut block = u.clone();
+        for _ in 1..iterations {
+            u = hmac(digest, password, &u);
+            for (b, x) in block.iter_mut().zip(&u) {
+                *b ^= x;
+            }
+        }
+        derived.extend_from_slice(&block);
+        index += 1;
+    }
+    derived.truncate(key_len);
+    derived
+}
+
+/// Salsa20/8 core (RFC 7914 section 3) applied in place.
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    for _ in 0..4 {
+        for [a, b, c, d] in [
+            [0, 4, 8, 12],
+            [5, 9, 13, 1],
+            [10, 14, 2, 6],
+            [15, 3, 7, 11],
+        ] {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+        for [a, b, c, d] in [
+            [0, 1, 2, 3],
+            [5, 6, 7, 4],
+            [10, 11, 8, 9],
+            [15, 12, 13, 14],
+        ] {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+    }
+    for (word, value) in block.iter_mut().zip(x) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// scryptBlockMix over `2 * r` 64-byte blocks held as words.
+fn block_mix(input: &[u32], output: &mut [u32], r: usize) {
+    let mut x: [u32; 16] = input[(2 * r - 1) * 16..].try_into().unwrap();
+    for i in 0..2 * r {
+        for (word, value) in x.iter_mut().zip(&input[i * 16..(i + 1) * 16]) {
+            *word ^= value;
+        }
+        salsa20_8(&mut x);
+        // Even blocks go to the first half, odd ones to the second.
+        let slot = (i / 2 + (i % 2) * r) * 16;
+        output[slot..slot + 16].copy_from_slice(&x);
+    }
+}
+
+/// scryptROMix on one `128 * r` byte chunk.
+fn ro_mix(chunk: &mut [u8], n: usize, r: usize) {
+    let words = 32 * r;
+    let mut x: Vec<u32> = chunk
+        .chunks(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    let mut v = vec![0u32; words * n];
+    let mut scratch = vec![0u32; words];
+
+    for i in 0..n {
+        v[i * words..(i + 1) * words].copy_from_slice(&x);
+        block_mix(&x, &mut scratch, r);
+        std::mem::swap(&mut x, &mut scratch);
+    }
+    for _ in 0..n {
+        let j = x[words - 16] as usize & (n - 1);
+        for (word, value) in x.iter_mut().zip(&v[j * words..(j + 1) * words]) {
+            *word ^= value;
+        }
+        block_mix(&x, &mut scratch, r);
+        std::mem::swap(&mut x, &mut scratch);
+    }
+    for (bytes, word) in chunk.chunks_mut(4).zip(x) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+/// scrypt (RFC 7914). `n` must be a power of two greater than one; the
+/// memory needed is about `128 * r * (n + p)` bytes and may not exceed
+/// `max_memory`.
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: usize,
+    p: usize,
+    max_memory: u64,
+    key_len: usize,
+) -> Result<Vec<u8>, String> {
+    if n < 2 || !n.is_power_of_two() {
+        return Err("scrypt N must be a power of two greater than 1".to_string());
+    }
+    if r == 0 || p == 0 || (r as u64) * (p as u64) >= 1 << 30 {
+        return Err("invalid scrypt r or p".to_string());
+    }
+    // The `p` PBKDF2 output blocks plus ROMix's `n + 2` blocks of scratch,
+    // counted the way OpenSSL does before allocating any of them.
+    if (p as u128 + n as u128 + 2) * (r as u128) * 128 > max_memory as u128 {
+        return Err("scrypt parameters need too much memory".to_string());
+    }
+
+    let mut blocks = pbkdf2(Digest::Sha256, password, salt, 1, p * 128 * r);
+    for chunk in blocks.chunks_mut(128 * r) {
+        ro_mix(chunk, n as usize, r);
+    }
+    Ok(pbkdf2(Digest::Sha256, password, &blocks, 1, key_len))
+}
+
+/// OpenSSL's default `maxmem_bytes` for the scrypt KDF.
+pub const MAX_SCRYPT_MEMORY: u64 = 1025 * 1024 * 1024;
+
+/// The memory OpenSSL lets scrypt use when decrypting a PBES2 key.
+pub const MAX_PBE_SCRYPT_MEMORY: u64 = 32 * 1024 * 1024;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evp_bytes_to_key_matches_openssl() {
+        // openssl enc -des-ede3-cbc -md md5 -pass pass:password -S 0102030405060708 -P
+        let (key, iv) = evp_bytes_to_key(
+            Digest::Md5,
+            b"password",
+            Some(&[1, 2, 3, 4, 5, 6, 7, 8]),
+            24,
+            8,
+        );
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+
+        assert_eq!(hex(&key), "E7B0971E52CA5CC8D0539FB3412F6316F7BA2E6EE293D9F3");
+        assert_eq!(hex(&iv), "457B99436B51CE02");
+    }
+
+    #[test]
+    fn test_pbkdf2_rfc6070() {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+        assert_eq!(
+            hex(&pbkdf2(Digest::Sha1, b"password", b"salt", 2, 20)),
+            "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"
+        );
+        assert_eq!(
+            hex(&pbkdf2(Digest::Sha1, b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, 25)),
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_rfc7914() {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+        assert_eq!(
+            hex(&pbkdf2(Digest::Sha256, b"passwd", b"salt", 1, 64)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+    }
+
+    #[test]
+    fn test_scrypt_rfc7914() {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+        assert_eq!(
+            hex(&scrypt(b"", b"", 16, 1, 1, MAX_SCRYPT_MEMORY, 64).unwrap()),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+             fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+        assert_eq!(
+            hex(&scrypt(b"password", b"NaCl", 1024, 8, 16, MAX_SCRYPT_MEMORY, 64).unwrap()),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn test_scrypt_rejects_bad_parameters() {
+        assert!(scrypt(b"", b"", 15, 1, 1, MAX_SCRYPT_MEMORY, 16).is_err());
+        assert!(scrypt(b"", b"", 16, 0, 1, MAX_SCRYPT_MEMORY, 16).is_err());
+        assert!(scrypt(b"", b"", 1 << 21, 8, 1, MAX_SCRYPT_MEMORY, 16).is_err());
+        assert!(scrypt(b"", b"", 2, 8, 1_000_000, MAX_PBE_SCRYPT_MEMORY, 16).is_err());
+        assert!(scrypt(b"", b"", 2, 8, (1 << 30) / 8 - 1, MAX_SCRYPT_MEMORY, 16).is_err());
+    }
+}
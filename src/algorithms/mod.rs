@@ -1,2 +1,14 @@
+pub mod aes;
+pub mod cipher;
+pub mod des;
+pub mod digest;
+pub mod gcm;
+pub mod hmac;
+pub mod kdf;
+pub mod md5;
 pub mod primality;
+pub mod rng;
 pub mod rsa;
+pub mod rsa_padding;
+pub mod sha1;
+pub mod sha256;
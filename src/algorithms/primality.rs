@@ -1,98 +1,700 @@
-use num::{BigInt, One, Zero};
-use modpow::*;
-use rand::Rng;
+use num::bigint::Sign;
+use num::{BigInt, One, Signed, ToPrimitive, Zero};
+use super::digest::Digest;
+use super::rng::CryptoRng;
+use super::rsa::to_bytes_padded;
+use std::sync::OnceLock;
 
-pub fn is_prime(n: u64, k: usize) -> bool {
-    if k > 100 {
-        panic!("Probability value must be between 0 to 100, got {}.", k);
-    }
-    if n <= 3 {
-        return n > 1;
+/// Bound on the odd primes used to sieve candidates before Miller-Rabin.
+const SMALL_PRIME_LIMIT: u64 = 1 << 14;
+/// How far a sieved search walks from its random start before redrawing.
+const SEARCH_STEPS: u64 = 1 << 16;
+
+/// Miller-Rabin bases that decide primality exactly for every n < 2^64.
+const DETERMINISTIC_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn mul_mod(a: u64, b: u64, n: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(n)) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, n: u64) -> u64 {
+    let mut result = 1;
+    base %= n;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, n);
+        }
+        base = mul_mod(base, base, n);
+        exponent >>= 1;
     }
-    if n % 2 == 0 {
+    result
+}
+
+/// Exact primality for 64-bit integers: Miller-Rabin with the first twelve
+/// primes as bases has no counterexample below 2^64.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
         return false;
     }
+    for base in DETERMINISTIC_BASES {
+        if n.is_multiple_of(base) {
+            return n == base;
+        }
+    }
 
     // Write n − 1 as 2^s·d with d odd by factoring powers of 2 from n − 1
-    let mut d = n - 1;
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for base in DETERMINISTIC_BASES {
+        let mut x = pow_mod(base, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Miller-Rabin over arbitrary-size integers with `rounds` random bases.
+/// Numbers below 2^64 get the exact `is_prime` instead.
+pub fn is_probable_prime(n: &BigInt, rounds: usize, rng: &mut dyn CryptoRng) -> bool {
+    if let Some(small) = n.to_u64() {
+        return is_prime(small);
+    }
+    let two = BigInt::from(2);
+    if n < &two {
+        return false;
+    }
+    if n < &BigInt::from(4) {
+        return true;
+    }
+    if (n % &two).is_zero() {
+        return false;
+    }
+
+    let n_minus_one = n - BigInt::one();
+    let mut d = n_minus_one.clone();
     let mut s = 0;
-    while d % 2 == 0 {
-        d /= 2;
+    while (&d % &two).is_zero() {
+        d /= &two;
         s += 1;
     }
 
-    let mut rng = rand::thread_rng();
+    let mut bytes = vec![0u8; n.bits().div_ceil(8) + 8];
 
-    for _ in 0..k {
-        let a: u32 = rng.gen_range(2..u32::try_from(n).unwrap() - 1);
-        let mut x = modpow(&a.into(), &d, &n);
+    'witness: for _ in 0..rounds {
+        rng.fill_bytes(&mut bytes);
+        let a = BigInt::from_bytes_be(Sign::Plus, &bytes) % (n - BigInt::from(3)) + &two;
+        let mut x = a.modpow(&d, n);
 
-        let tmp: BigInt = n.try_into().unwrap();
-        if x == BigInt::one() || x == tmp.clone() - BigInt::one() {
+        if x.is_one() || x == n_minus_one {
             continue;
         }
-
-        let mut composite = true;
         for _ in 1..s {
-            x = modpow(&x, &BigInt::from(2), &n);
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Miller-Rabin rounds for a random `bits`-bit candidate, from FIPS 186-4
+/// table C.3 (error probability at most 2^-100).
+pub fn prime_checks_for_size(bits: usize) -> usize {
+    match bits {
+        3747.. => 3,
+        1345.. => 4,
+        476.. => 5,
+        400.. => 6,
+        347.. => 7,
+        308.. => 8,
+        55.. => 27,
+        _ => 34,
+    }
+}
+
+/// How `prime` and other callers decide primality.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrimalityTest {
+    /// Miller-Rabin with this many random bases.
+    MillerRabin { rounds: usize },
+    /// Baillie-PSW: deterministic, with no known counterexample.
+    #[default]
+    BailliePsw,
+}
+
+impl PrimalityTest {
+    pub fn is_prime(&self, n: &BigInt, rng: &mut dyn CryptoRng) -> bool {
+        match *self {
+            PrimalityTest::MillerRabin { rounds } => is_probable_prime(n, rounds, rng),
+            PrimalityTest::BailliePsw => is_baillie_psw_prime(n),
+        }
+    }
+}
+
+/// Number of trailing zero bits of non-zero `n`.
+fn trailing_zeros(n: &BigInt) -> usize {
+    let bytes = n.to_bytes_le().1;
+    let zero_bytes = bytes.iter().take_while(|&&byte| byte == 0).count();
+    zero_bytes * 8 + bytes[zero_bytes].trailing_zeros() as usize
+}
+
+/// Strong probable prime test of odd `n > 3` to the given base.
+pub fn is_strong_probable_prime(n: &BigInt, base: &BigInt) -> bool {
+    let n_minus_one = n - BigInt::one();
+    let s = trailing_zeros(&n_minus_one);
+    let d = &n_minus_one >> s;
+
+    let mut x = base.modpow(&d, n);
+    if x.is_one() || x == n_minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = x.modpow(&BigInt::from(2), n);
+        if x == n_minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// Jacobi symbol (a/n) for odd positive `n`.
+fn jacobi(a: &BigInt, n: &BigInt) -> i32 {
+    let mut a = a % n;
+    if a.sign() == Sign::Minus {
+        a += n;
+    }
+    let mut n = n.clone();
+    let mut result = 1;
+    while !a.is_zero() {
+        let twos = trailing_zeros(&a);
+        a >>= twos;
+        let n_mod_8 = (&n % 8u32).to_u32().unwrap();
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        if (&a % 4u32).to_u32() == Some(3) && (&n % 4u32).to_u32() == Some(3) {
+            result = -result;
+        }
+        a %= &n;
+    }
+    if n.is_one() {
+        result
+    } else {
+        0
+    }
+}
+
+fn is_perfect_square(n: &BigInt) -> bool {
+    let root = n.sqrt();
+    &root * &root == *n
+}
 
-            if x == tmp.clone() - BigInt::one() {
-                composite = false;
+/// Strong Lucas probable prime test of odd, non-square `n > 3`, with
+/// Selfridge's parameters: the first D in 5, -7, 9, -11, ... with
+/// (D/n) = -1, P = 1 and Q = (1 - D) / 4.
+pub fn is_strong_lucas_probable_prime(n: &BigInt) -> bool {
+    let mut d = BigInt::from(5);
+    loop {
+        match jacobi(&d, n) {
+            -1 => break,
+            0 if d.abs() != *n => return false,
+            _ => (),
+        }
+        d = if d.sign() == Sign::Minus { -d + 2 } else { -d - 2 };
+    }
+    let reduce = |value: BigInt| {
+        let value = value % n;
+        if value.sign() == Sign::Minus {
+            value + n
+        } else {
+            value
+        }
+    };
+    let halve = |value: BigInt| {
+        if (&value % 2u32).is_zero() {
+            (value >> 1) % n
+        } else {
+            ((value + n) >> 1) % n
+        }
+    };
+    let q = reduce((BigInt::one() - &d) / 4);
+    let d = reduce(d);
+
+    // n + 1 = k * 2^s with k odd; walk U_k, V_k and Q^k from the top bit.
+    let n_plus_one = n + BigInt::one();
+    let s = trailing_zeros(&n_plus_one);
+    let k = &n_plus_one >> s;
+    let (mut u, mut v, mut q_k) = (BigInt::one(), BigInt::one(), q.clone());
+    for bit in k.to_str_radix(2).bytes().skip(1) {
+        u = (&u * &v) % n;
+        v = reduce(&v * &v - (&q_k << 1));
+        q_k = (&q_k * &q_k) % n;
+        if bit == b'1' {
+            let next_u = halve(&u + &v);
+            v = halve(reduce(&d * &u + &v));
+            u = next_u;
+            q_k = (&q_k * &q) % n;
+        }
+    }
+
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = reduce(&v * &v - (&q_k << 1));
+        if v.is_zero() {
+            return true;
+        }
+        q_k = (&q_k * &q_k) % n;
+    }
+    false
+}
+
+/// Baillie-PSW: trial division, a strong probable prime test to base 2 and
+/// a strong Lucas test.
+pub fn is_baillie_psw_prime(n: &BigInt) -> bool {
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for prime in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if (n % prime).is_zero() {
+            return *n == BigInt::from(prime);
+        }
+    }
+    is_strong_probable_prime(n, &BigInt::from(2))
+        && !is_perfect_square(n)
+        && is_strong_lucas_probable_prime(n)
+}
+
+/// The odd primes below `SMALL_PRIME_LIMIT`.
+pub fn small_primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        let limit = SMALL_PRIME_LIMIT as usize;
+        let mut composite = vec![false; limit];
+        let mut primes = Vec::new();
+        for n in (3..limit).step_by(2) {
+            if !composite[n] {
+                primes.push(n as u64);
+                for multiple in (n * n..limit).step_by(n) {
+                    composite[multiple] = true;
+                }
+            }
+        }
+        primes
+    })
+}
+
+/// Residues of `start + k * step` modulo each small prime, updated as `k`
+/// grows so that most composites are rejected without big-number
+/// arithmetic.
+pub struct Sieve {
+    residues: Vec<u64>,
+    steps: Vec<u64>,
+}
+
+impl Sieve {
+    pub fn new(start: &BigInt, step: &BigInt) -> Sieve {
+        let reduce = |value: &BigInt, prime: u64| (value % prime).to_u64().unwrap();
+        let primes = small_primes();
+        Sieve {
+            residues: primes.iter().map(|&prime| reduce(start, prime)).collect(),
+            steps: primes.iter().map(|&prime| reduce(step, prime)).collect(),
+        }
+    }
+
+    /// Moves to the next candidate, `start + (k + 1) * step`.
+    pub fn advance(&mut self) {
+        for ((residue, step), &prime) in self.residues.iter_mut().zip(&self.steps).zip(small_primes()) {
+            *residue += step;
+            if *residue >= prime {
+                *residue -= prime;
+            }
+        }
+    }
+
+    /// Whether `accept(prime, residue)` holds for every small prime.
+    pub fn passes(&self, accept: impl Fn(u64, u64) -> bool) -> bool {
+        self.residues.iter().zip(small_primes()).all(|(&residue, &prime)| accept(prime, residue))
+    }
+}
+
+/// Random odd `bits`-bit number with the top two bits set.
+fn random_candidate(bits: usize, rng: &mut dyn CryptoRng) -> BigInt {
+    let mut bytes = vec![0u8; bits.div_ceil(8)];
+    rng.fill_bytes(&mut bytes);
+    let mut candidate = BigInt::from_bytes_be(Sign::Plus, &bytes) >> (bytes.len() * 8 - bits);
+    candidate |= BigInt::from(3) << (bits - 2);
+    candidate | BigInt::one()
+}
+
+/// Random probable prime of exactly `bits` bits with the top two bits set,
+/// so that the product of two such primes has exactly `2 * bits` bits.
+///
+/// From a random odd start the search steps p += 2, keeping p's residues
+/// modulo the small primes up to date, and only runs Miller-Rabin on
+/// candidates with no small factor.
+pub fn generate_probable_prime(bits: usize, rng: &mut dyn CryptoRng) -> BigInt {
+    let rounds = prime_checks_for_size(bits);
+    let two = BigInt::from(2);
+    // Below this size the candidate could itself be one of the small primes.
+    let use_sieve = bits > 16;
+
+    loop {
+        let mut candidate = random_candidate(bits, rng);
+        let mut sieve = Sieve::new(&candidate, &two);
+        for _ in 0..SEARCH_STEPS {
+            if candidate.bits() != bits {
                 break;
             }
+            let sieved = !use_sieve || sieve.passes(|_, residue| residue != 0);
+            if sieved && is_probable_prime(&candidate, rounds, rng) {
+                return candidate;
+            }
+            candidate += &two;
+            sieve.advance();
         }
+    }
+}
 
-        if composite {
-            return false;
+/// Random safe prime of exactly `bits` bits: p = 2q + 1 with q prime.
+/// There are none with the top two bits set below 6 bits, except 7.
+///
+/// The search walks q upwards and sieves q and p together: q must not be
+/// divisible by a small prime r, nor be (r - 1) / 2 modulo r, which would
+/// make r divide p.
+pub fn generate_safe_prime(bits: usize, rng: &mut dyn CryptoRng) -> Result<BigInt, String> {
+    if bits < 6 && bits != 3 {
+        return Err(format!("no {}-bit safe prime", bits));
+    }
+    let rounds = prime_checks_for_size(bits);
+    let two = BigInt::from(2);
+    // Below this size q could itself be one of the small primes.
+    let use_sieve = bits > 16;
+
+    loop {
+        let mut q = random_candidate(bits - 1, rng);
+        let mut sieve = Sieve::new(&q, &two);
+        for _ in 0..SEARCH_STEPS {
+            if q.bits() != bits - 1 {
+                break;
+            }
+            let sieved = !use_sieve
+                || sieve.passes(|prime, residue| residue != 0 && residue != (prime - 1) / 2);
+            if sieved {
+                let p = (&q << 1) + BigInt::one();
+                // One round on each first, since most candidates fail there.
+                if is_probable_prime(&q, 1, rng)
+                    && is_probable_prime(&p, 1, rng)
+                    && is_probable_prime(&q, rounds, rng)
+                    && is_probable_prime(&p, rounds, rng)
+                {
+                    return Ok(p);
+                }
+            }
+            q += &two;
+            sieve.advance();
         }
     }
+}
 
-    true
+/// Auxiliary prime sizes for a prime of `bits` bits, from the FIPS 186-4
+/// table B.1 minimums for moduli of `2 * bits` bits.
+fn auxiliary_prime_bits(bits: usize) -> usize {
+    match bits {
+        1536.. => 171,
+        1024.. => 141,
+        _ => 101,
+    }
+}
+
+/// A strong prime with its auxiliary primes: `p_minus_factor` divides p - 1
+/// and `p_plus_factor` divides p + 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StrongPrime {
+    pub prime: BigInt,
+    pub p_minus_factor: BigInt,
+    pub p_plus_factor: BigInt,
 }
 
-pub fn get_prime() -> u64 {
-    let mut rng = rand::thread_rng();
-    let mut num: u32 = rng.gen();
+/// Random strong prime of exactly `bits` bits in the style of ANSI X9.31:
+/// two auxiliary primes p1 and p2 are drawn, and p is searched in the
+/// progression p = 1 mod p1, p = -1 mod p2, with gcd(p - 1, e) = 1 so that
+/// it can serve as an RSA factor for `public_exponent`.
+pub fn generate_strong_prime(
+    bits: usize,
+    public_exponent: &BigInt,
+    rng: &mut dyn CryptoRng,
+) -> Result<StrongPrime, String> {
+    let aux_bits = auxiliary_prime_bits(bits);
+    if bits < 2 * aux_bits + 64 {
+        return Err(format!("strong primes need at least {} bits", 2 * aux_bits + 64));
+    }
+    let rounds = prime_checks_for_size(bits);
+    let one = BigInt::one();
+
+    loop {
+        let p1 = generate_probable_prime(aux_bits, rng);
+        let p2 = generate_probable_prime(aux_bits, rng);
+        if p1 == p2 {
+            continue;
+        }
+        // R = 1 mod p1 and R = -1 mod p2, made odd so that stepping by
+        // 2 * p1 * p2 only visits odd candidates.
+        let product = &p1 * &p2;
+        let mut r = (mod_inverse(p2.clone(), p1.clone()) * &p2
+            - mod_inverse(p1.clone(), p2.clone()) * &p1)
+            % &product;
+        if r.sign() == Sign::Minus {
+            r += &product;
+        }
+        if (&r % 2u32).is_zero() {
+            r += &product;
+        }
+        let step = &product << 1;
 
-    while !is_prime(num.into(), 5) {
-        num = rng.gen();
+        let start = random_candidate(bits, rng);
+        let mut offset = (&r - &start) % &step;
+        if offset.sign() == Sign::Minus {
+            offset += &step;
+        }
+        let mut p = start + offset;
+        let mut sieve = Sieve::new(&p, &step);
+        for _ in 0..SEARCH_STEPS {
+            if p.bits() != bits {
+                break;
+            }
+            if sieve.passes(|_, residue| residue != 0)
+                && gcd(&(&p - &one), public_exponent).is_one()
+                && is_probable_prime(&p, rounds, rng)
+            {
+                return Ok(StrongPrime {
+                    prime: p,
+                    p_minus_factor: p1,
+                    p_plus_factor: p2,
+                });
+            }
+            p += &step;
+            sieve.advance();
+        }
     }
-    num.into()
 }
 
-pub fn generate_prime() -> (u64, u64) {
-    let mut p: u64;
-    let mut q: u64;
+/// One Pocklington step of a primality certificate: `factor` is a proven
+/// prime dividing `prime - 1` with factor^2 > prime, and `witness` is a base
+/// a with a^(prime-1) = 1 and gcd(a^((prime-1)/factor) - 1, prime) = 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PocklingtonStep {
+    pub prime: BigInt,
+    pub factor: BigInt,
+    pub witness: BigInt,
+}
 
+/// Proof that a prime is prime: a prime below 2^33, checked exactly, and
+/// Pocklington steps each building on the prime before it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrimeCertificate {
+    pub base: u64,
+    pub steps: Vec<PocklingtonStep>,
+}
+
+impl PrimeCertificate {
+    /// The prime this certificate proves.
+    pub fn prime(&self) -> BigInt {
+        match self.steps.last() {
+            Some(step) => step.prime.clone(),
+            None => BigInt::from(self.base),
+        }
+    }
+}
+
+/// Checks every link of `certificate` and that it proves `prime`.
+pub fn verify_prime_certificate(prime: &BigInt, certificate: &PrimeCertificate) -> Result<(), String> {
+    if !is_prime(certificate.base) {
+        return Err(format!("base {} is not prime", certificate.base));
+    }
+    let mut proven = BigInt::from(certificate.base);
+    for step in &certificate.steps {
+        let n = &step.prime;
+        let n_minus_one = n - BigInt::one();
+        if step.factor != proven {
+            return Err("certificate chain is broken".to_string());
+        }
+        let a = &step.witness;
+        if *n <= BigInt::from(3) || *a < BigInt::from(2) || *a > &n_minus_one - BigInt::one() {
+            return Err(format!("{} is not a valid witness for {}", a, n));
+        }
+        if !(&n_minus_one % &step.factor).is_zero() || &step.factor * &step.factor <= *n {
+            return Err(format!("{} is not a large enough factor of {} - 1", step.factor, n));
+        }
+        if !a.modpow(&n_minus_one, n).is_one() {
+            return Err(format!("{} fails Fermat's test to base {}", n, a));
+        }
+        let partial = a.modpow(&(&n_minus_one / &step.factor), n) - BigInt::one();
+        if !gcd(&partial, n).is_one() {
+            return Err(format!("witness {} does not prove {}", a, n));
+        }
+        proven = n.clone();
+    }
+    if proven != *prime {
+        return Err("certificate proves a different number".to_string());
+    }
+    Ok(())
+}
+
+/// Hash(seed + offset) with the seed kept at its original width.
+fn seed_hash(seed: &BigInt, offset: u64, seed_len: usize) -> BigInt {
+    let value = (seed + offset) % (BigInt::one() << (8 * seed_len));
+    BigInt::from_bytes_be(Sign::Plus, &Digest::Sha256.hash(&to_bytes_padded(&value, seed_len)))
+}
+
+/// `iterations + 1` consecutive seed hashes concatenated into one integer,
+/// least significant first.
+fn seed_hashes(seed: &BigInt, iterations: u64, seed_len: usize) -> BigInt {
+    let outlen = Digest::Sha256.output_len() * 8;
+    (0..=iterations).fold(BigInt::zero(), |sum, i| {
+        sum + (seed_hash(seed, i, seed_len) << (i as usize * outlen))
+    })
+}
+
+/// Shawe-Taylor random prime routine (FIPS 186-4 appendix C.6, FIPS 186-5
+/// appendix A.1.2) with SHA-256. Returns the certificate, the next prime
+/// seed and the prime generation counter.
+fn shawe_taylor(
+    bits: usize,
+    seed: &BigInt,
+    seed_len: usize,
+) -> Result<(PrimeCertificate, BigInt, usize), String> {
+    let failure = || Err(format!("Shawe-Taylor failed to find a {}-bit prime", bits));
+    if bits < 2 {
+        return failure();
+    }
+    if bits < 33 {
+        let mut prime_seed = seed.clone();
+        let mut counter = 0;
+        loop {
+            let c = seed_hash(&prime_seed, 0, seed_len) ^ seed_hash(&prime_seed, 1, seed_len);
+            let high = 1u64 << (bits - 1);
+            let c = (high + (c % high).to_u64().unwrap()) | 1;
+            counter += 1;
+            prime_seed += 2;
+            if is_prime(c) {
+                let certificate = PrimeCertificate {
+                    base: c,
+                    steps: Vec::new(),
+                };
+                return Ok((certificate, prime_seed, counter));
+            }
+            if counter > 4 * bits {
+                return failure();
+            }
+        }
+    }
+
+    let (mut certificate, mut prime_seed, mut counter) = shawe_taylor(bits.div_ceil(2) + 1, seed, seed_len)?;
+    let c0 = certificate.prime();
+    let outlen = Digest::Sha256.output_len() * 8;
+    let iterations = (bits.div_ceil(outlen) - 1) as u64;
+    let old_counter = counter;
+    let high = BigInt::one() << (bits - 1);
+
+    let x = seed_hashes(&prime_seed, iterations, seed_len);
+    prime_seed += iterations + 1;
+    let x = &high + x % &high;
+    let two_c0 = &c0 << 1;
+    let ceil_div = |a: &BigInt, b: &BigInt| (a + b - BigInt::one()) / b;
+    let mut t = ceil_div(&x, &two_c0);
     loop {
-        //p += 1;
-        p = get_prime();
-        q = get_prime();
+        if &two_c0 * &t + BigInt::one() > (&high << 1) {
+            t = ceil_div(&high, &two_c0);
+        }
+        let c = &two_c0 * &t + BigInt::one();
+        counter += 1;
+
+        let a = seed_hashes(&prime_seed, iterations, seed_len);
+        prime_seed += iterations + 1;
+        let a = BigInt::from(2) + a % (&c - BigInt::from(3));
+        let z = a.modpow(&(&t << 1), &c);
+        if gcd(&(&z - BigInt::one()), &c).is_one() && z.modpow(&c0, &c).is_one() {
+            certificate.steps.push(PocklingtonStep {
+                prime: c,
+                factor: c0,
+                witness: a,
+            });
+            return Ok((certificate, prime_seed, counter));
+        }
+        if counter >= 4 * bits + old_counter {
+            return failure();
+        }
+        t += 1;
+    }
+}
+
+/// Provable prime of exactly `bits` bits derived from `seed` by the
+/// Shawe-Taylor algorithm, with the certificate that proves it. The same
+/// seed always gives the same prime.
+pub fn generate_provable_prime(bits: usize, seed: &[u8]) -> Result<(BigInt, PrimeCertificate), String> {
+    if seed.is_empty() {
+        return Err("empty seed".to_string());
+    }
+    let seed_value = BigInt::from_bytes_be(Sign::Plus, seed);
+    let (certificate, _, _) = shawe_taylor(bits, &seed_value, seed.len())?;
+    Ok((certificate.prime(), certificate))
+}
 
-        print!(".");
-        let modules: u64 = p * q;
+/// Random prime below 2^32. Walks upwards over odd numbers from a random
+/// start, trial dividing by the small primes before running `is_prime`.
+pub fn get_prime(rng: &mut dyn CryptoRng) -> u64 {
+    loop {
+        let mut num = u64::from(rng.next_u32() | 1);
+        while num <= u64::from(u32::MAX) {
+            let has_small_factor = small_primes()
+                .iter()
+                .take_while(|&&prime| prime * prime <= num)
+                .any(|&prime| num.is_multiple_of(prime));
+            if !has_small_factor && is_prime(num) {
+                return num;
+            }
+            num += 2;
+        }
+    }
+}
 
-        if modules >> 63 == 1 {
-            println!("+++++");
-            break;
+/// Two primes below 2^32 whose product has exactly 64 bits.
+pub fn generate_prime(rng: &mut dyn CryptoRng) -> (u64, u64) {
+    loop {
+        let p = get_prime(rng);
+        let q = get_prime(rng);
+        if (p * q) >> 63 == 1 {
+            return (p, q);
         }
     }
-    println!("new line");
-    (p.into(), q.into())
 }
 
 pub fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
     if b == &BigInt::zero() {
         return a.clone();
     }
-    return gcd(b, &(a % b));
+    gcd(b, &(a % b))
 }
 
 pub fn lcm(a: &BigInt, b: &BigInt) -> BigInt {
     if b == &BigInt::zero() {
         return a.clone();
     }
-    return (a * b) / gcd(a, b);
+    (a * b) / gcd(a, b)
 }
 
 pub fn mod_inverse(mut a: BigInt, mut b: BigInt) -> BigInt {
@@ -117,9 +719,10 @@ pub fn mod_inverse(mut a: BigInt, mut b: BigInt) -> BigInt {
     if x < BigInt::zero() {
         x += m;
     }
-    return x;
+    x
 }
 
+#[allow(dead_code)]
 fn int_to_bytes(mut num: u64) -> Vec<u8> {
     let mut bytes = vec![0, 0, 0, 0, 0, 0, 0, 0];
     let mut i = 7;
@@ -136,6 +739,204 @@ fn int_to_bytes(mut num: u64) -> Vec<u8> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::algorithms::rng::SystemRng;
+
+    #[test]
+    fn test_generate_safe_prime() {
+        let mut rng = SystemRng::new().unwrap();
+        for bits in [3, 6, 64] {
+            let p = generate_safe_prime(bits, &mut rng).unwrap();
+            assert_eq!(p.bits(), bits);
+            assert!(is_probable_prime(&p, 20, &mut rng));
+            assert!(is_probable_prime(&(p >> 1), 20, &mut rng));
+        }
+        assert!(generate_safe_prime(5, &mut rng).is_err());
+
+        let p = generate_safe_prime(256, &mut rng).unwrap();
+        assert_eq!(p.bits(), 256);
+        assert!(is_probable_prime(&(p >> 1), 20, &mut rng));
+    }
+
+    #[test]
+    fn test_generate_probable_prime_sizes() {
+        let mut rng = SystemRng::new().unwrap();
+        for bits in [2, 3, 8, 16, 17, 64, 512] {
+            let p = generate_probable_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert_eq!(&p >> (bits - 2), BigInt::from(3));
+            assert!(is_probable_prime(&p, 20, &mut rng));
+        }
+        for _ in 0..20 {
+            let p = get_prime(&mut rng);
+            assert!(p <= u64::from(u32::MAX));
+            assert!(is_prime(p));
+        }
+    }
+
+    #[test]
+    fn test_baillie_psw_matches_trial_division() {
+        let mut composite = vec![false; 20000];
+        for n in 2..composite.len() {
+            if !composite[n] {
+                for multiple in (n * n..composite.len()).step_by(n) {
+                    composite[multiple] = true;
+                }
+            }
+            assert_eq!(is_baillie_psw_prime(&BigInt::from(n)), !composite[n], "{}", n);
+        }
+        assert!(!is_baillie_psw_prime(&BigInt::from(0)));
+        assert!(!is_baillie_psw_prime(&BigInt::from(1)));
+    }
+
+    #[test]
+    fn test_baillie_psw_pseudoprimes() {
+        // Strong pseudoprimes to base 2, including one to bases 2 through 7.
+        for n in [2047u64, 3277, 4033, 3215031751] {
+            let n = BigInt::from(n);
+            assert!(is_strong_probable_prime(&n, &BigInt::from(2)));
+            assert!(!is_baillie_psw_prime(&n));
+        }
+        // Strong Lucas pseudoprimes, OEIS A217255.
+        for n in [5459u64, 5777, 10877, 16109, 18971] {
+            let n = BigInt::from(n);
+            assert!(is_strong_lucas_probable_prime(&n));
+            assert!(!is_baillie_psw_prime(&n));
+        }
+        // Carmichael numbers and the square of a prime.
+        for n in [561u64, 41041, 825265, 1194649] {
+            assert!(!is_baillie_psw_prime(&BigInt::from(n)));
+        }
+        let mersenne = (BigInt::one() << 127) - 1u32;
+        assert!(is_baillie_psw_prime(&mersenne));
+        assert!(!is_baillie_psw_prime(&(&mersenne * &mersenne)));
+        assert!(!is_baillie_psw_prime(&((BigInt::one() << 128) - 1u32)));
+    }
+
+    #[test]
+    fn test_primality_test_selection() {
+        let mut rng = SystemRng::new().unwrap();
+        let prime = BigInt::from(3197041061052982553u64);
+        assert_eq!(PrimalityTest::default(), PrimalityTest::BailliePsw);
+        for test in [PrimalityTest::BailliePsw, PrimalityTest::MillerRabin { rounds: 20 }] {
+            assert!(test.is_prime(&prime, &mut rng));
+            assert!(!test.is_prime(&(&prime * 3u32), &mut rng));
+        }
+    }
+
+    #[test]
+    fn test_is_prime_is_exact_for_u64() {
+        let mut composite = vec![false; 100_000];
+        for n in 2..composite.len() {
+            if !composite[n] {
+                for multiple in (n * n..composite.len()).step_by(n) {
+                    composite[multiple] = true;
+                }
+            }
+            assert_eq!(is_prime(n as u64), !composite[n], "{}", n);
+        }
+        // Strong pseudoprimes to every prime base up to 17 and up to 23.
+        assert!(!is_prime(341550071728321));
+        assert!(!is_prime(3825123056546413051));
+        assert!(is_prime(18446744073709551557));
+        assert!(!is_prime(u64::MAX));
+        assert!(!is_prime(4294967291 * 4294967279));
+    }
+
+    #[test]
+    fn test_generate_provable_prime() {
+        let seed = Digest::Sha256.hash(b"provable prime seed");
+        for bits in [2, 20, 33, 64, 512] {
+            let (prime, certificate) = generate_provable_prime(bits, &seed).unwrap();
+            assert_eq!(prime.bits(), bits);
+            assert!(is_baillie_psw_prime(&prime));
+            assert_eq!(verify_prime_certificate(&prime, &certificate), Ok(()));
+            assert_eq!(certificate.steps.is_empty(), bits < 33);
+        }
+        let (prime, _) = generate_provable_prime(256, &seed).unwrap();
+        assert_eq!(generate_provable_prime(256, &seed).unwrap().0, prime);
+        assert_ne!(generate_provable_prime(256, b"another seed").unwrap().0, prime);
+        assert!(generate_provable_prime(1, &seed).is_err());
+        assert!(generate_provable_prime(256, b"").is_err());
+    }
+
+    #[test]
+    fn test_verify_prime_certificate_rejects_tampering() {
+        let (prime, certificate) = generate_provable_prime(256, b"tamper").unwrap();
+        assert!(verify_prime_certificate(&(&prime + 2u32), &certificate).is_err());
+
+        let mut bad_witness = certificate.clone();
+        bad_witness.steps.last_mut().unwrap().witness = BigInt::one();
+        assert!(verify_prime_certificate(&prime, &bad_witness).is_err());
+
+        let mut broken_chain = certificate.clone();
+        broken_chain.steps.remove(0);
+        assert!(verify_prime_certificate(&prime, &broken_chain).is_err());
+
+        let mut composite_base = certificate;
+        composite_base.base += 1;
+        assert!(verify_prime_certificate(&prime, &composite_base).is_err());
+    }
+
+    #[test]
+    fn test_verify_prime_certificate_rejects_out_of_range_steps() {
+        let negative = PrimeCertificate {
+            base: 2,
+            steps: vec![PocklingtonStep {
+                prime: BigInt::from(-1),
+                factor: BigInt::from(2),
+                witness: BigInt::from(2),
+            }],
+        };
+        assert!(verify_prime_certificate(&BigInt::from(-1), &negative).is_err());
+
+        let (prime, certificate) = generate_provable_prime(256, b"range").unwrap();
+        for witness in [BigInt::zero(), &prime - 1u32, prime.clone()] {
+            let mut bad_witness = certificate.clone();
+            bad_witness.steps.last_mut().unwrap().witness = witness;
+            assert!(verify_prime_certificate(&prime, &bad_witness).is_err());
+        }
+    }
+
+    #[test]
+    fn test_small_primes() {
+        let primes = small_primes();
+        assert_eq!(&primes[..6], &[3, 5, 7, 11, 13, 17]);
+        assert_eq!(primes.len(), 1899);
+        assert!(primes.iter().all(|&p| is_prime(p)));
+    }
+
+    #[test]
+    fn test_sieve_tracks_residues() {
+        let start = BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap();
+        let step = BigInt::from(1_000_002u64);
+        let mut sieve = Sieve::new(&start, &step);
+        for _ in 0..100 {
+            sieve.advance();
+        }
+        let value = start + step * 100u32;
+        for (&prime, &residue) in small_primes().iter().zip(&sieve.residues) {
+            assert_eq!(BigInt::from(residue), &value % prime);
+        }
+        assert!(!Sieve::new(&BigInt::from(3 * 20011), &BigInt::from(2)).passes(|_, r| r != 0));
+        assert!(Sieve::new(&BigInt::from(20011), &BigInt::from(2)).passes(|_, r| r != 0));
+    }
+
+    #[test]
+    fn test_generate_strong_prime() {
+        let mut rng = SystemRng::new().unwrap();
+        let e = BigInt::from(65537);
+        let strong = generate_strong_prime(512, &e, &mut rng).unwrap();
+        let p = &strong.prime;
+        assert_eq!(p.bits(), 512);
+        assert!(is_probable_prime(p, 20, &mut rng));
+        assert_eq!(strong.p_minus_factor.bits(), 101);
+        assert_eq!(strong.p_plus_factor.bits(), 101);
+        assert!(((p - 1u32) % &strong.p_minus_factor).is_zero());
+        assert!(((p + 1u32) % &strong.p_plus_factor).is_zero());
+        assert!(gcd(&(p - 1u32), &e).is_one());
+        assert!(generate_strong_prime(256, &e, &mut rng).is_err());
+    }
+
     #[test]
     fn test_mod_inverse() {
         let p = BigInt::from(3672915913u64);
@@ -161,4 +962,4 @@ fn test_some() {
     let n = p.clone() * q.clone();
     let e = lcm(&(p.clone() - BigInt::one()), &(q.clone() - 1));
     println!("n = {}, and e = {}", n, e);
-}
\ No newline at end of file
+}
@@ -0,0 +1,257 @@
+use super::digest::Digest;
+use super::hmac::hmac;
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+// HMAC_DRBG from NIST SP 800-90A Rev. 1, section 10.1.2, with SHA-256.
+
+/// Requests between reseeds allowed by SP 800-90A table 2.
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+/// Largest single request, 2^19 bits.
+pub const MAX_REQUEST_BYTES: usize = 1 << 16;
+
+const ENTROPY_LEN: usize = 32;
+const NONCE_LEN: usize = 16;
+const PERSONALIZATION: &[u8] = b"ft_ssl HMAC_DRBG";
+
+/// Source of every random byte used for keys, padding, salts and IVs.
+pub trait CryptoRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_be_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+pub struct HmacDrbg {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    reseed_counter: u64,
+    reseed_interval: u64,
+}
+
+impl HmacDrbg {
+    /// Instantiates the DRBG from `entropy`, `nonce` and an optional
+    /// personalization string.
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> HmacDrbg {
+        let len = Digest::Sha256.output_len();
+        let mut drbg = HmacDrbg {
+            key: vec![0x00; len],
+            value: vec![0x01; len],
+            reseed_counter: 1,
+            reseed_interval: RESEED_INTERVAL,
+        };
+        drbg.update(&[entropy, nonce, personalization].concat());
+        drbg
+    }
+
+    /// A DRBG driven only by `seed`, so that everything drawn from it is
+    /// reproducible. For tests and bug reports, never for real keys.
+    pub fn from_seed(seed: &[u8]) -> HmacDrbg {
+        HmacDrbg::new(seed, &[], b"ft_ssl seeded HMAC_DRBG")
+    }
+
+    /// Lowers the number of requests allowed before `generate` insists on a
+    /// reseed.
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        self.reseed_interval = interval;
+    }
+
+    pub fn needs_reseed(&self) -> bool {
+        self.reseed_counter > self.reseed_interval
+    }
+
+    pub fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) {
+        self.update(&[entropy, additional_input].concat());
+        self.reseed_counter = 1;
+    }
+
+    /// Fills `output`, which may be at most `MAX_REQUEST_BYTES` long. Fails
+    /// once the reseed interval is exhausted.
+    pub fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), String> {
+        if output.len() > MAX_REQUEST_BYTES {
+            return Err(format!("request too large: {} bytes", output.len()));
+        }
+        if self.needs_reseed() {
+            return Err("reseed required".to_string());
+        }
+        if !additional_input.is_empty() {
+            self.update(additional_input);
+        }
+        for chunk in output.chunks_mut(self.value.len()) {
+            self.value = hmac(Digest::Sha256, &self.key, &self.value);
+            chunk.copy_from_slice(&self.value[..chunk.len()]);
+        }
+        self.update(additional_input);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    fn update(&mut self, provided_data: &[u8]) {
+        for round in [0x00u8, 0x01] {
+            if round == 0x01 && provided_data.is_empty() {
+                break;
+            }
+            let input = [&self.value[..], &[round], provided_data].concat();
+            self.key = hmac(Digest::Sha256, &self.key, &input);
+            self.value = hmac(Digest::Sha256, &self.key, &self.value);
+        }
+    }
+}
+
+impl CryptoRng for HmacDrbg {
+    /// Panics once the reseed interval runs out, since there is no entropy
+    /// source to reseed from.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(MAX_REQUEST_BYTES) {
+            if let Err(reason) = self.generate(chunk, &[]) {
+                panic!("HMAC_DRBG: {}", reason);
+            }
+        }
+    }
+}
+
+/// Reads `buf.len()` bytes of entropy from the operating system.
+pub fn os_entropy(buf: &mut [u8]) -> Result<(), String> {
+    OsRng.try_fill_bytes(buf).map_err(|err| err.to_string())
+}
+
+/// The HMAC_DRBG seeded from the operating system, reseeding itself from
+/// the OS whenever its reseed interval runs out.
+pub struct SystemRng {
+    drbg: HmacDrbg,
+}
+
+impl SystemRng {
+    pub fn new() -> Result<SystemRng, String> {
+        let mut seed = [0u8; ENTROPY_LEN + NONCE_LEN];
+        os_entropy(&mut seed)?;
+        let (entropy, nonce) = seed.split_at(ENTROPY_LEN);
+        Ok(SystemRng {
+            drbg: HmacDrbg::new(entropy, nonce, PERSONALIZATION),
+        })
+    }
+
+    pub fn reseed(&mut self) -> Result<(), String> {
+        let mut entropy = [0u8; ENTROPY_LEN];
+        os_entropy(&mut entropy)?;
+        self.drbg.reseed(&entropy, &[]);
+        Ok(())
+    }
+
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        self.drbg.set_reseed_interval(interval);
+    }
+}
+
+impl CryptoRng for SystemRng {
+    /// Panics if the OS stops providing entropy at a reseed, as
+    /// `rand::thread_rng` does.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(MAX_REQUEST_BYTES) {
+            if self.drbg.needs_reseed() {
+                if let Err(reason) = self.reseed() {
+                    panic!("could not reseed from OS entropy: {}", reason);
+                }
+            }
+            // Neither limit can be hit after the checks above.
+            self.drbg.generate(chunk, &[]).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_cavp_hmac_drbg_sha256() {
+        // HMAC_DRBG.rsp, SHA-256, no prediction resistance, COUNT = 0.
+        let mut drbg = HmacDrbg::new(
+            &from_hex("ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488"),
+            &from_hex("659ba96c601dc69fc902940805ec0ca8"),
+            &[],
+        );
+        let mut output = [0u8; 128];
+        drbg.generate(&mut output, &[]).unwrap();
+        drbg.generate(&mut output, &[]).unwrap();
+        assert_eq!(
+            output.to_vec(),
+            from_hex(
+                "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+                 d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+                 07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+                 961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reseed_interval_is_enforced() {
+        let mut drbg = HmacDrbg::new(&[1; ENTROPY_LEN], &[2; NONCE_LEN], &[]);
+        drbg.set_reseed_interval(2);
+        let mut output = [0u8; 16];
+        assert!(drbg.generate(&mut output, &[]).is_ok());
+        assert!(drbg.generate(&mut output, &[]).is_ok());
+        assert_eq!(drbg.generate(&mut output, &[]), Err("reseed required".to_string()));
+        drbg.reseed(&[3; ENTROPY_LEN], &[]);
+        assert!(drbg.generate(&mut output, &[]).is_ok());
+        let mut too_large = vec![0u8; MAX_REQUEST_BYTES + 1];
+        assert!(drbg.generate(&mut too_large, &[]).is_err());
+    }
+
+    #[test]
+    fn test_reseed_and_additional_input_change_the_stream() {
+        let generate = |reseed: bool, additional_input: &[u8]| {
+            let mut drbg = HmacDrbg::new(&[1; ENTROPY_LEN], &[2; NONCE_LEN], &[]);
+            if reseed {
+                drbg.reseed(&[3; ENTROPY_LEN], &[]);
+            }
+            let mut output = [0u8; 32];
+            drbg.generate(&mut output, additional_input).unwrap();
+            output
+        };
+        assert_eq!(generate(false, &[]), generate(false, &[]));
+        assert_ne!(generate(false, &[]), generate(true, &[]));
+        assert_ne!(generate(false, &[]), generate(false, b"extra"));
+    }
+
+    #[test]
+    fn test_seeded_drbg_is_reproducible() {
+        let draw = |seed: &[u8]| {
+            let mut rng = HmacDrbg::from_seed(seed);
+            let mut output = vec![0u8; MAX_REQUEST_BYTES + 16];
+            rng.fill_bytes(&mut output);
+            (output, rng.next_u64())
+        };
+        assert_eq!(draw(b"seed"), draw(b"seed"));
+        assert_ne!(draw(b"seed"), draw(b"seed2"));
+    }
+
+    #[test]
+    fn test_system_rng_reseeds_itself() {
+        let mut rng = SystemRng::new().unwrap();
+        rng.set_reseed_interval(1);
+        let mut first = vec![0u8; MAX_REQUEST_BYTES + 100];
+        let mut second = vec![0u8; MAX_REQUEST_BYTES + 100];
+        rng.fill_bytes(&mut first);
+        rng.fill_bytes(&mut second);
+        assert_ne!(first, second);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+}
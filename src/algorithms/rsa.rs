@@ -1 +1,433 @@
+use crate::algorithms::primality::{
+    gcd, generate_probable_prime, is_probable_prime, lcm, mod_inverse, prime_checks_for_size, Sieve,
+};
+use crate::algorithms::rng::CryptoRng;
+use num::bigint::Sign;
+use num::{BigInt, One, Zero};
 
+#[derive(Clone, Debug)]
+pub struct RsaKey {
+    pub modulus: BigInt,
+    pub public_exponent: BigInt,
+    pub private_exponent: BigInt,
+    /// p, q, then any further primes r_3, r_4, ... of a multi-prime key.
+    pub prime: Vec<BigInt>,
+    /// d mod (r_i - 1) for each prime.
+    pub exponent: Vec<BigInt>,
+    /// q^-1 mod p, then t_i = (r_1 * ... * r_(i-1))^-1 mod r_i for each
+    /// prime past the second (RFC 8017 section 3.2).
+    pub coefficient: Vec<BigInt>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    pub modulus: BigInt,
+    pub public_exponent: BigInt,
+}
+
+impl RsaKey {
+    pub fn public_key(&self) -> RsaPublicKey {
+        RsaPublicKey {
+            modulus: self.modulus.clone(),
+            public_exponent: self.public_exponent.clone(),
+        }
+    }
+
+    pub fn bits(&self) -> usize {
+        self.modulus.bits()
+    }
+}
+
+impl RsaPublicKey {
+    pub fn bits(&self) -> usize {
+        self.modulus.bits()
+    }
+}
+
+/// The consistency checks behind `rsa -check`, reported with OpenSSL's
+/// wording.
+pub fn check_key(key: &RsaKey, rng: &mut dyn CryptoRng) -> Result<(), String> {
+    let one = BigInt::one();
+    let primes = &key.prime;
+    if primes.len() < 2 || key.exponent.len() != primes.len() || key.coefficient.len() + 1 != primes.len() {
+        return Err("invalid multi prime key".to_string());
+    }
+
+    for (i, prime) in primes.iter().enumerate() {
+        if !is_probable_prime(prime, 64, rng) {
+            return Err(match i {
+                0 => "p not prime",
+                1 => "q not prime",
+                _ => "mp r not prime",
+            }
+            .to_string());
+        }
+    }
+    if primes.iter().product::<BigInt>() != key.modulus {
+        return Err(match primes.len() {
+            2 => "n does not equal p q",
+            _ => "n does not equal product of primes",
+        }
+        .to_string());
+    }
+    let lambda = primes.iter().fold(one.clone(), |lambda, prime| lcm(&lambda, &(prime - &one)));
+    if (&key.private_exponent * &key.public_exponent) % &lambda != one {
+        return Err("d e not congruent to 1".to_string());
+    }
+    for (i, (prime, exponent)) in primes.iter().zip(&key.exponent).enumerate() {
+        if exponent != &(&key.private_exponent % (prime - &one)) {
+            return Err(match i {
+                0 => "dmp1 not congruent to d",
+                1 => "dmq1 not congruent to d",
+                _ => "mp exponent not congruent to d",
+            }
+            .to_string());
+        }
+    }
+    let coefficients = crt_coefficients(primes);
+    if let Some(i) = key.coefficient.iter().zip(&coefficients).position(|(a, b)| a != b) {
+        return Err(match i {
+            0 => "iqmp not inverse of q",
+            _ => "mp coefficient not inverse of r",
+        }
+        .to_string());
+    }
+    Ok(())
+}
+
+/// The CRT coefficients stored in a key: q^-1 mod p, then
+/// (r_1 * ... * r_(i-1))^-1 mod r_i for every further prime.
+fn crt_coefficients(primes: &[BigInt]) -> Vec<BigInt> {
+    let mut coefficients = vec![mod_inverse(primes[1].clone(), primes[0].clone())];
+    let mut product = &primes[0] * &primes[1];
+    for prime in &primes[2..] {
+        coefficients.push(mod_inverse(&product % prime, prime.clone()));
+        product *= prime;
+    }
+    coefficients
+}
+
+/// Big-endian `value` left-padded with zeros to `len` bytes (I2OSP).
+pub fn to_bytes_padded(value: &BigInt, len: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be().1;
+    let mut padded = vec![0u8; len.saturating_sub(bytes.len())];
+    padded.extend_from_slice(&bytes);
+    padded
+}
+
+/// Raw RSA with the public exponent: `input^e mod n`, as `k` bytes where
+/// `k` is the modulus length.
+pub fn public_operation(key: &RsaPublicKey, input: &[u8]) -> Result<Vec<u8>, String> {
+    let value = BigInt::from_bytes_be(Sign::Plus, input);
+    if value >= key.modulus {
+        return Err("data too large for modulus".to_string());
+    }
+    let k = key.bits().div_ceil(8);
+    Ok(to_bytes_padded(&value.modpow(&key.public_exponent, &key.modulus), k))
+}
+
+/// Raw RSA with the private key through the CRT over every prime
+/// (RFC 8017 section 5.1.2).
+pub fn private_operation(key: &RsaKey, input: &[u8]) -> Result<Vec<u8>, String> {
+    let value = BigInt::from_bytes_be(Sign::Plus, input);
+    if value >= key.modulus {
+        return Err("data too large for modulus".to_string());
+    }
+    let reduce = |value: BigInt, modulus: &BigInt| {
+        let value = value % modulus;
+        if value.sign() == Sign::Minus {
+            value + modulus
+        } else {
+            value
+        }
+    };
+    let (p, q) = (&key.prime[0], &key.prime[1]);
+    let m1 = value.modpow(&key.exponent[0], p);
+    let m2 = value.modpow(&key.exponent[1], q);
+    let h = reduce(&key.coefficient[0] * (m1 - &m2), p);
+    let mut m = m2 + h * q;
+    let mut product = p * q;
+    let others = key.prime[2..].iter().zip(&key.exponent[2..]).zip(&key.coefficient[1..]);
+    for ((prime, exponent), coefficient) in others {
+        let mi = value.modpow(exponent, prime);
+        let h = reduce(coefficient * (mi - &m), prime);
+        m += &product * h;
+        product *= prime;
+    }
+    let k = key.bits().div_ceil(8);
+    Ok(to_bytes_padded(&m, k))
+}
+
+/// Generates a two-prime key with a `bits`-bit modulus and the given public
+/// exponent, which must be odd and greater than one.
+pub fn generate_key(
+    bits: usize,
+    public_exponent: &BigInt,
+    rng: &mut dyn CryptoRng,
+) -> Result<RsaKey, String> {
+    generate_multi_prime_key(bits, 2, public_exponent, rng)
+}
+
+/// Most primes OpenSSL allows in a key of `bits` bits.
+pub fn max_primes(bits: usize) -> usize {
+    match bits {
+        8192.. => 5,
+        4096.. => 4,
+        1024.. => 3,
+        _ => 2,
+    }
+}
+
+/// Generates a key whose `bits`-bit modulus is the product of `primes`
+/// distinct primes of about `bits / primes` bits each.
+pub fn generate_multi_prime_key(
+    bits: usize,
+    primes: usize,
+    public_exponent: &BigInt,
+    rng: &mut dyn CryptoRng,
+) -> Result<RsaKey, String> {
+    let one = BigInt::one();
+    if bits < 16 {
+        return Err(format!("key size too small: {} bits", bits));
+    }
+    if primes < 2 || primes > max_primes(bits) {
+        return Err(format!("invalid number of primes for a {} bit key: {}", bits, primes));
+    }
+    if public_exponent <= &one || (public_exponent % 2u32).is_zero() {
+        return Err("pub exponent out of range".to_string());
+    }
+
+    let coprime = |prime: &BigInt| gcd(public_exponent, &(prime - &one)).is_one();
+    let mut found: Vec<BigInt> = Vec::with_capacity(primes);
+    for i in 0..primes {
+        let prime_bits = bits / primes + usize::from(i < bits % primes);
+        loop {
+            let prime = generate_probable_prime(prime_bits, rng);
+            if !coprime(&prime) || found.contains(&prime) {
+                continue;
+            }
+            // Past two primes the top bits no longer guarantee the size.
+            if i + 1 == primes && (found.iter().product::<BigInt>() * &prime).bits() != bits {
+                continue;
+            }
+            found.push(prime);
+            break;
+        }
+    }
+    if found[0] < found[1] {
+        found.swap(0, 1);
+    }
+    Ok(key_from_primes(found, public_exponent.clone()))
+}
+
+/// Smallest modulus FIPS 186-5 allows for key generation.
+pub const FIPS_MIN_BITS: usize = 2048;
+
+/// Generates a two-prime key as FIPS 186-5 appendix A.1.3 (B.3.3 in
+/// FIPS 186-4) describes: random primes of exactly `bits / 2` bits, each at
+/// least sqrt(2) * 2^(bits/2 - 1), with |p - q| > 2^(bits/2 - 100) and
+/// d > 2^(bits/2). The key then has to pass a pairwise consistency test.
+pub fn generate_fips_key(
+    bits: usize,
+    public_exponent: &BigInt,
+    rng: &mut dyn CryptoRng,
+) -> Result<RsaKey, String> {
+    if bits < FIPS_MIN_BITS || !bits.is_multiple_of(2) {
+        return Err(format!("key size not allowed in FIPS mode: {} bits", bits));
+    }
+    let e = public_exponent;
+    if e <= &(BigInt::one() << 16) || e.bits() > 256 || (e % 2u32).is_zero() {
+        return Err("public exponent not allowed in FIPS mode".to_string());
+    }
+
+    let half = bits / 2;
+    let min_distance = BigInt::one() << (half - 100);
+    loop {
+        let p = fips_prime(half, e, None, 5 * half, rng)?;
+        let q = fips_prime(half, e, Some((&p, &min_distance)), 10 * half, rng)?;
+        let (p, q) = if p > q { (p, q) } else { (q, p) };
+        let key = key_from_primes(vec![p, q], e.clone());
+        // Step 3 of A.1.1: a small d means starting over with new primes.
+        if key.private_exponent.bits() > half {
+            pairwise_consistency_test(&key, rng)?;
+            return Ok(key);
+        }
+    }
+}
+
+/// One prime of a FIPS key. `other` is p with the minimum distance when
+/// this is q; the search gives up after `attempts` candidates.
+fn fips_prime(
+    bits: usize,
+    public_exponent: &BigInt,
+    other: Option<(&BigInt, &BigInt)>,
+    attempts: usize,
+    rng: &mut dyn CryptoRng,
+) -> Result<BigInt, String> {
+    let one = BigInt::one();
+    let rounds = prime_checks_for_size(bits);
+    let mut bytes = vec![0u8; bits.div_ceil(8)];
+    for _ in 0..attempts {
+        rng.fill_bytes(&mut bytes);
+        let candidate = (BigInt::from_bytes_be(Sign::Plus, &bytes) >> (bytes.len() * 8 - bits)) | &one;
+        // candidate >= sqrt(2) * 2^(bits - 1), squared to stay in integers.
+        if &candidate * &candidate < one.clone() << (2 * bits - 1) {
+            continue;
+        }
+        if let Some((p, min_distance)) = other {
+            let distance = if &candidate > p { &candidate - p } else { p - &candidate };
+            if &distance <= min_distance {
+                continue;
+            }
+        }
+        if !gcd(&(&candidate - &one), public_exponent).is_one() {
+            continue;
+        }
+        let no_small_factor = Sieve::new(&candidate, &BigInt::zero()).passes(|_, residue| residue != 0);
+        if no_small_factor && is_probable_prime(&candidate, rounds, rng) {
+            return Ok(candidate);
+        }
+    }
+    Err("prime generation failed".to_string())
+}
+
+/// The pairwise consistency test of SP 800-56B section 6.4.1.1: a random
+/// m must survive m^e^d with both the CRT and the plain private exponent.
+pub fn pairwise_consistency_test(key: &RsaKey, rng: &mut dyn CryptoRng) -> Result<(), String> {
+    let k = key.bits().div_ceil(8);
+    let mut bytes = vec![0u8; k];
+    rng.fill_bytes(&mut bytes);
+    let message = (BigInt::from_bytes_be(Sign::Plus, &bytes) % (&key.modulus - 2u32)) + 2u32;
+    let encrypted = public_operation(&key.public_key(), &to_bytes_padded(&message, k))?;
+    let decrypted = private_operation(key, &encrypted)?;
+    let direct = BigInt::from_bytes_be(Sign::Plus, &encrypted).modpow(&key.private_exponent, &key.modulus);
+    if BigInt::from_bytes_be(Sign::Plus, &decrypted) != message || direct != message {
+        return Err("pairwise consistency test failed".to_string());
+    }
+    Ok(())
+}
+
+/// Completes a key from its primes, with `d` reduced modulo
+/// lcm(r_1 - 1, ..., r_u - 1).
+pub fn key_from_primes(primes: Vec<BigInt>, public_exponent: BigInt) -> RsaKey {
+    let one = BigInt::one();
+    let lambda = primes.iter().fold(one.clone(), |lambda, prime| lcm(&lambda, &(prime - &one)));
+    let private_exponent = mod_inverse(public_exponent.clone(), lambda);
+
+    RsaKey {
+        modulus: primes.iter().product(),
+        exponent: primes.iter().map(|prime| &private_exponent % (prime - &one)).collect(),
+        coefficient: crt_coefficients(&primes),
+        public_exponent,
+        private_exponent,
+        prime: primes,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithms::rng::SystemRng;
+
+    #[test]
+    fn test_generated_key_is_consistent() {
+        let mut rng = SystemRng::new().unwrap();
+        for bits in [64, 65, 512] {
+            let key = generate_key(bits, &BigInt::from(65537), &mut rng).unwrap();
+            assert_eq!(key.bits(), bits);
+            assert!(key.prime[0] > key.prime[1]);
+            assert_eq!(check_key(&key, &mut rng), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_private_operation_inverts_public_operation() {
+        let mut rng = SystemRng::new().unwrap();
+        let key = generate_key(512, &BigInt::from(65537), &mut rng).unwrap();
+        let message = b"raw RSA round trip";
+
+        let encrypted = public_operation(&key.public_key(), message).unwrap();
+        assert_eq!(encrypted.len(), 64);
+        let decrypted = private_operation(&key, &encrypted).unwrap();
+        assert_eq!(&decrypted[64 - message.len()..], message);
+        assert!(decrypted[..64 - message.len()].iter().all(|&b| b == 0));
+        assert!(public_operation(&key.public_key(), &[0xff; 64]).is_err());
+    }
+
+    #[test]
+    fn test_generate_key_rejects_bad_parameters() {
+        let mut rng = SystemRng::new().unwrap();
+        assert!(generate_key(8, &BigInt::from(65537), &mut rng).is_err());
+        assert!(generate_key(512, &BigInt::from(65536), &mut rng).is_err());
+        assert!(generate_key(512, &BigInt::from(1), &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_multi_prime_key_is_consistent() {
+        let mut rng = SystemRng::new().unwrap();
+        let key = generate_multi_prime_key(1024, 3, &BigInt::from(65537), &mut rng).unwrap();
+        assert_eq!(key.bits(), 1024);
+        assert_eq!(key.prime.len(), 3);
+        assert_eq!(check_key(&key, &mut rng), Ok(()));
+
+        // The CRT over three primes agrees with the plain private exponent.
+        let message = b"three prime round trip";
+        let encrypted = public_operation(&key.public_key(), message).unwrap();
+        let decrypted = private_operation(&key, &encrypted).unwrap();
+        assert_eq!(&decrypted[128 - message.len()..], message);
+        let direct = BigInt::from_bytes_be(Sign::Plus, &encrypted).modpow(&key.private_exponent, &key.modulus);
+        assert_eq!(BigInt::from_bytes_be(Sign::Plus, &decrypted), direct);
+
+        let mut broken = key.clone();
+        broken.coefficient[1] += 1;
+        assert_eq!(check_key(&broken, &mut rng), Err("mp coefficient not inverse of r".to_string()));
+        let mut broken = key;
+        broken.exponent[2] += 2;
+        assert_eq!(check_key(&broken, &mut rng), Err("mp exponent not congruent to d".to_string()));
+    }
+
+    #[test]
+    fn test_prime_count_follows_key_size() {
+        let mut rng = SystemRng::new().unwrap();
+        let f4 = BigInt::from(65537);
+        assert_eq!([512, 1024, 4096, 8192].map(max_primes), [2, 3, 4, 5]);
+        assert!(generate_multi_prime_key(512, 3, &f4, &mut rng).is_err());
+        assert!(generate_multi_prime_key(1024, 1, &f4, &mut rng).is_err());
+        assert!(generate_multi_prime_key(2048, 4, &f4, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_fips_key_meets_the_186_5_criteria() {
+        let mut rng = SystemRng::new().unwrap();
+        let key = generate_fips_key(2048, &BigInt::from(65537), &mut rng).unwrap();
+        let (p, q) = (&key.prime[0], &key.prime[1]);
+        let floor = BigInt::one() << 2047;
+        assert_eq!(key.bits(), 2048);
+        assert!(p * p >= floor && q * q >= floor);
+        assert!(p - q > BigInt::one() << 924);
+        assert!(key.private_exponent > BigInt::one() << 1024);
+        assert_eq!(check_key(&key, &mut rng), Ok(()));
+        assert_eq!(pairwise_consistency_test(&key, &mut rng), Ok(()));
+    }
+
+    #[test]
+    fn test_fips_key_rejects_bad_parameters() {
+        let mut rng = SystemRng::new().unwrap();
+        let f4 = BigInt::from(65537);
+        assert!(generate_fips_key(1024, &f4, &mut rng).is_err());
+        assert!(generate_fips_key(2049, &f4, &mut rng).is_err());
+        assert!(generate_fips_key(2048, &BigInt::from(3), &mut rng).is_err());
+        assert!(generate_fips_key(2048, &BigInt::from(65536), &mut rng).is_err());
+        assert!(generate_fips_key(2048, &((BigInt::one() << 256) + 1), &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_pairwise_consistency_test_catches_a_broken_key() {
+        let mut rng = SystemRng::new().unwrap();
+        let mut key = generate_key(512, &BigInt::from(65537), &mut rng).unwrap();
+        assert_eq!(pairwise_consistency_test(&key, &mut rng), Ok(()));
+        key.private_exponent += 2;
+        key.exponent[0] += 2;
+        assert!(pairwise_consistency_test(&key, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use super::genrsa::{parse_public_exponent, RSA_F4};
+use super::io::{key_rng, parse_seed, SEEDED_WARNING};
+use super::key::{
+    encrypt_private_key_pem, parse_cipher, private_key_pem, private_key_text,
+    write_key, KeyFormat,
+};
+use super::options::{parse_args, unknown_option, Value};
+use super::password::{get_password, prompt_password};
+use crate::algorithms::cipher::Cipher;
+use crate::algorithms::rsa::generate_multi_prime_key;
+use crate::encoding::pkcs8::Pbes2Params;
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+use num::BigInt;
+
+pub struct GenpkeyOption {
+    algorithm: Option<String>,
+    bits: usize,
+    primes: usize,
+    public_exponent: BigInt,
+    outfile: Option<String>,
+    outform: KeyFormat,
+    pass: Option<String>,
+    cipher: Option<Cipher>,
+    seed: Option<Vec<u8>>,
+    text: bool,
+}
+
+impl Default for GenpkeyOption {
+    fn default() -> Self {
+        GenpkeyOption {
+            algorithm: None,
+            bits: 2048,
+            primes: 2,
+            public_exponent: BigInt::from(RSA_F4),
+            outfile: None,
+            outform: KeyFormat::Pem,
+            pass: None,
+            cipher: None,
+            seed: None,
+            text: false,
+        }
+    }
+}
+
+const GENPKEY_OPTIONS: &[OptionDef] = &[
+    OptionDef::new("algorithm", ValueType::Str, "The public key algorithm"),
+    OptionDef::new("pkeyopt", ValueType::Str, "Set the public key algorithm option as opt:value"),
+    OptionDef::new("out", ValueType::OutFile, "Output (private key) file"),
+    OptionDef::new("outform", ValueType::Format, "Output format (DER or PEM)"),
+    OptionDef::new("pass", ValueType::Pass, "Output file pass phrase source"),
+    OptionDef::new("cipher", ValueType::Str, "Cipher to use to encrypt the key"),
+    OptionDef::flag("*", "Same as -cipher with the given CBC cipher"),
+    OptionDef::new("seed", ValueType::Str, "Seed the key generation with the given hex string"),
+    OptionDef::new("rand", ValueType::Str, "Seed the key generation with the given file(s)"),
+    OptionDef::flag("text", "Print the private key in text"),
+];
+
+pub struct Genpkey;
+
+impl Command for Genpkey {
+    fn name(&self) -> &str {
+        "genpkey"
+    }
+
+    fn usage(&self) -> &str {
+        "[options]"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        GENPKEY_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        genpkey_command(args)
+    }
+}
+
+pub fn genpkey_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_genpkey_options(args)?;
+    match option.algorithm.as_deref() {
+        Some("RSA") => (),
+        Some(name) => return Err(FtSslError::Usage(format!("Error initializing {} context", name))),
+        None => return Err(FtSslError::Usage("genpkey: no algorithm specified".to_string())),
+    }
+
+    if option.seed.is_some() {
+        eprintln!("{}", SEEDED_WARNING);
+    }
+    let mut rng = key_rng(option.seed.as_deref())?;
+    let key = generate_multi_prime_key(option.bits, option.primes, &option.public_exponent, rng.as_mut())
+        .map_err(|reason| FtSslError::Operation(format!("Error generating RSA key: {}", reason)))?;
+
+    let mut pem = private_key_pem(&key, option.outform == KeyFormat::Der);
+    if let Some(cipher) = option.cipher {
+        let password = match option.pass.as_deref().map(get_password) {
+            Some(password) => password.map_err(|err| err.context("Error getting password"))?,
+            None => prompt_password("Enter PEM pass phrase:", true)?,
+        };
+        let params = Pbes2Params {
+            cipher,
+            ..Pbes2Params::default()
+        };
+        pem = encrypt_private_key_pem(&key, false, &params, password.as_bytes())?;
+    }
+    write_key(option.outfile.as_ref(), option.outform, &pem)?;
+    if option.text {
+        print!("{}", private_key_text(&key));
+    }
+    Ok(())
+}
+
+pub fn parse_genpkey_options(args: &[String]) -> Result<GenpkeyOption, FtSslError> {
+    let mut option = GenpkeyOption::default();
+
+    for (name, value) in parse_args("genpkey", GENPKEY_OPTIONS, args)?.options {
+        match (name, value) {
+            ("text", _) => option.text = true,
+            ("algorithm", Value::Str(algorithm)) => {
+                let name = algorithm.to_ascii_uppercase();
+                option.algorithm = Some(match name.as_str() {
+                    "RSAENCRYPTION" => "RSA".to_string(),
+                    _ => name,
+                });
+            }
+            ("pkeyopt", Value::Str(pkeyopt)) => parse_pkeyopt(&mut option, &pkeyopt)?,
+            ("out", Value::File(file)) => option.outfile = Some(file),
+            ("outform", Value::Format(format)) => option.outform = format,
+            ("pass", Value::Pass(source)) => option.pass = Some(source),
+            ("seed" | "rand", Value::Str(seed)) => option
+                .seed
+                .get_or_insert_with(Vec::new)
+                .extend(parse_seed(&format!("-{}", name), &seed)?),
+            ("cipher", Value::Str(cipher)) => option.cipher = Some(parse_cipher(&cipher)?),
+            ("*", Value::Str(cipher)) => match Cipher::from_name(&cipher).filter(Cipher::is_cbc) {
+                Some(cipher) => option.cipher = Some(cipher),
+                None => return Err(unknown_option("genpkey", &format!("-{}", cipher))),
+            },
+            (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+        }
+    }
+    Ok(option)
+}
+
+fn parse_pkeyopt(option: &mut GenpkeyOption, pkeyopt: &str) -> Result<(), FtSslError> {
+    let parsed = match pkeyopt.split_once(':') {
+        Some(("rsa_keygen_bits", value)) => value.parse().map(|bits| option.bits = bits).is_ok(),
+        Some(("rsa_keygen_pubexp", value)) => {
+            parse_public_exponent(value).map(|e| option.public_exponent = e).is_some()
+        }
+        Some(("rsa_keygen_primes", value)) => value.parse().map(|primes| option.primes = primes).is_ok(),
+        _ => false,
+    };
+    if !parsed {
+        return Err(FtSslError::Usage(format!("Error setting {} parameter", pkeyopt)));
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::algorithms::rng::{CryptoRng, HmacDrbg, SystemRng};
+use crate::error::FtSslError;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+/// Reads all of `infile`, or standard input when no file is given.
+pub fn read_input(infile: Option<&String>) -> Result<Vec<u8>, FtSslError> {
+    let mut content = Vec::new();
+    let result = match infile {
+        Some(filename) => File::open(filename).and_then(|mut file| file.read_to_end(&mut content)),
+        None => io::stdin().read_to_end(&mut content),
+    };
+    match result {
+        Ok(_) => Ok(content),
+        Err(err) => Err(FtSslError::Io(format!("error reading input file: {}", err))),
+    }
+}
+
+/// Opens `outfile` for writing, or standard output when no file is given.
+pub fn open_output(outfile: Option<&String>) -> Result<Box<dyn Write>, FtSslError> {
+    match outfile {
+        Some(filename) => match File::create(filename) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(err) => Err(FtSslError::Io(format!("error writing output file: {}", err))),
+        },
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// Writes `data` to `outfile`, or standard output when no file is given.
+pub fn write_output(outfile: Option<&String>, data: &[u8]) -> Result<(), FtSslError> {
+    let result = match outfile {
+        Some(filename) => File::create(filename).and_then(|mut file| file.write_all(data)),
+        None => io::stdout().write_all(data),
+    };
+    result.map_err(|err| FtSslError::Io(format!("error writing output file: {}", err)))
+}
+
+/// The OS-seeded DRBG behind every command that needs randomness.
+pub fn system_rng() -> Result<SystemRng, FtSslError> {
+    SystemRng::new()
+        .map_err(|reason| FtSslError::Io(format!("unable to seed random number generator: {}", reason)))
+}
+
+/// How much of each `-rand` file seeds the DRBG, so that devices such as
+/// `/dev/urandom` can be named and large files are not read whole.
+pub const SEED_FILE_LIMIT: u64 = 2048;
+
+/// Seed material from `-seed hex` or `-rand file[:file...]`.
+pub fn parse_seed(option: &str, value: &str) -> Result<Vec<u8>, FtSslError> {
+    if option == "-rand" {
+        let mut seed = Vec::new();
+        for file in value.split(':') {
+            File::open(file)
+                .and_then(|file| file.take(SEED_FILE_LIMIT).read_to_end(&mut seed))
+                .map_err(|err| FtSslError::Io(format!("error reading random file {}: {}", file, err)))?;
+        }
+        return Ok(seed);
+    }
+    let bytes: Option<Vec<u8>> = (0..value.len())
+        .step_by(2)
+        .map(|i| value.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect();
+    match bytes {
+        Some(bytes) if !bytes.is_empty() => Ok(bytes),
+        _ => Err(FtSslError::Usage(format!("Invalid hex seed: {}", value))),
+    }
+}
+
+/// What commands print before generating from `-seed` or `-rand`.
+pub const SEEDED_WARNING: &str = "Warning: seeded generation is reproducible, do not use the result as a real key";
+
+/// Randomness for key generation: the OS-seeded DRBG, or a deterministic
+/// one when `-seed` or `-rand` gave seed material.
+pub fn key_rng(seed: Option<&[u8]>) -> Result<Box<dyn CryptoRng>, FtSslError> {
+    match seed {
+        Some(seed) => Ok(Box::new(HmacDrbg::from_seed(seed))),
+        None => Ok(Box::new(system_rng()?)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rand_files_are_read_up_to_the_limit() {
+        let path = std::env::temp_dir().join(format!("ft_ssl_seed_{}", std::process::id()));
+        let content: Vec<u8> = (0..3 * SEED_FILE_LIMIT).map(|i| i as u8).collect();
+        std::fs::write(&path, &content).unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        let seed = parse_seed("-rand", &path).unwrap();
+        assert_eq!(seed, content[..SEED_FILE_LIMIT as usize]);
+        assert_eq!(parse_seed("-rand", &format!("{}:{}", path, path)).unwrap(), [&seed[..], &seed[..]].concat());
+        assert_eq!(parse_seed("-rand", "/dev/urandom").unwrap().len(), SEED_FILE_LIMIT as usize);
+        assert!(matches!(parse_seed("-rand", "/nonexistent/seed"), Err(FtSslError::Io(_))));
+        assert_eq!(parse_seed("-seed", "0aff").unwrap(), vec![0x0a, 0xff]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use super::io::write_output;
+use super::options::{extra_arguments, parse_args, Value};
+use crate::algorithms::digest::Digest;
+use crate::algorithms::kdf::{pbkdf2, scrypt, MAX_SCRYPT_MEMORY};
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+
+#[derive(Default)]
+pub struct KdfOption {
+    keylen: Option<usize>,
+    kdfopts: Vec<(String, String)>,
+    digest: Option<Digest>,
+    outfile: Option<String>,
+    binary: bool,
+    algorithm: Option<String>,
+}
+
+const KDF_OPTIONS: &[OptionDef] = &[
+    OptionDef::new("keylen", ValueType::PositiveInt, "The size of the output derived key"),
+    OptionDef::new("kdfopt", ValueType::Str, "KDF algorithm control parameters in n:v form"),
+    OptionDef::new("digest", ValueType::Str, "Digest, same as -kdfopt digest:name"),
+    OptionDef::new("out", ValueType::OutFile, "Output to specified file"),
+    OptionDef::flag("binary", "Output in binary format (default is hexadecimal)"),
+    OptionDef::parameter("kdf_name", "Name of the KDF algorithm"),
+];
+
+pub struct Kdf;
+
+impl Command for Kdf {
+    fn name(&self) -> &str {
+        "kdf"
+    }
+
+    fn usage(&self) -> &str {
+        "[options] kdf_name"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        KDF_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        kdf_command(args)
+    }
+}
+
+pub fn kdf_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_kdf_options(args)?;
+    let keylen = option
+        .keylen
+        .ok_or_else(|| FtSslError::Usage("kdf: Invalid key length".to_string()))?;
+    let algorithm = match &option.algorithm {
+        Some(algorithm) => algorithm.to_ascii_uppercase(),
+        None => return Err(FtSslError::Usage("kdf: No KDF specified".to_string())),
+    };
+
+    let derived = derive(&option, &algorithm, keylen).map_err(|reason| FtSslError::Usage(format!("kdf: {}", reason)))?;
+    if option.binary {
+        write_output(option.outfile.as_ref(), &derived)
+    } else {
+        let hex: Vec<String> = derived.iter().map(|b| format!("{:02X}", b)).collect();
+        write_output(option.outfile.as_ref(), format!("{}\n", hex.join(":")).as_bytes())
+    }
+}
+
+pub fn parse_kdf_options(args: &[String]) -> Result<KdfOption, FtSslError> {
+    let mut option = KdfOption::default();
+    let parsed = parse_args("kdf", KDF_OPTIONS, args)?;
+
+    for (name, value) in parsed.options {
+        match (name, value) {
+            ("binary", _) => option.binary = true,
+            ("keylen", Value::Int(keylen)) => option.keylen = Some(keylen as usize),
+            ("kdfopt", Value::Str(kdfopt)) => match kdfopt.split_once(':') {
+                Some((name, value)) => option.kdfopts.push((name.to_ascii_lowercase(), value.to_string())),
+                None => return Err(FtSslError::Usage(format!("kdf: Invalid control: {}", kdfopt))),
+            },
+            ("digest", Value::Str(digest)) => {
+                let digest = parse_digest(&digest).map_err(|reason| FtSslError::Usage(format!("kdf: {}", reason)))?;
+                option.digest = Some(digest);
+            }
+            ("out", Value::File(file)) => option.outfile = Some(file),
+            (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+        }
+    }
+    match parsed.parameters.as_slice() {
+        [] => (),
+        [algorithm] => option.algorithm = Some(algorithm.clone()),
+        _ => return Err(extra_arguments("kdf")),
+    }
+    Ok(option)
+}
+
+fn parse_digest(name: &str) -> Result<Digest, String> {
+    Digest::from_name(name).ok_or_else(|| format!("Unknown digest: {}", name))
+}
+
+/// Runs the named KDF over the `-kdfopt` controls, with OpenSSL's defaults
+/// for those left out.
+fn derive(option: &KdfOption, algorithm: &str, keylen: usize) -> Result<Vec<u8>, String> {
+    let mut password: Vec<u8> = Vec::new();
+    let mut salt: Vec<u8> = Vec::new();
+    let mut digest = option.digest.unwrap_or(Digest::Sha1);
+    let mut iterations: u64 = 2048;
+    let (mut n, mut r, mut p): (u64, usize, usize) = (1 << 20, 8, 1);
+    let mut max_memory = MAX_SCRYPT_MEMORY;
+
+    let number = |name: &str, value: &str| -> Result<u64, String> {
+        value
+            .parse()
+            .map_err(|_| format!("Invalid value for {}: {}", name, value))
+    };
+    for (name, value) in &option.kdfopts {
+        match (algorithm, name.as_str()) {
+            (_, "pass") => password = value.as_bytes().to_vec(),
+            (_, "hexpass") => password = decode_hex(value)?,
+            (_, "salt") => salt = value.as_bytes().to_vec(),
+            (_, "hexsalt") => salt = decode_hex(value)?,
+            ("PBKDF2", "digest") => digest = parse_digest(value)?,
+            ("PBKDF2", "iter") => iterations = number(name, value)?,
+            ("SCRYPT", "n") => n = number(name, value)?,
+            ("SCRYPT", "r") => r = number(name, value)? as usize,
+            ("SCRYPT", "p") => p = number(name, value)? as usize,
+            ("SCRYPT", "maxmem_bytes") => max_memory = number(name, value)?,
+            _ => return Err(format!("Unknown control: {}", name)),
+        }
+    }
+
+    match algorithm {
+        "PBKDF2" => {
+            if iterations == 0 {
+                return Err("Invalid iteration count".to_string());
+            }
+            Ok(pbkdf2(digest, &password, &salt, iterations, keylen))
+        }
+        "SCRYPT" => scrypt(&password, &salt, n, r, p, max_memory, keylen),
+        _ => Err(format!("Unsupported KDF: {}", algorithm)),
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex: String = hex.chars().filter(|&c| c != ':').collect();
+    if !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid hex value".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| Ok(u8::from_str_radix(&hex[i..i + 2], 16).unwrap()))
+        .collect()
+}
@@ -0,0 +1,233 @@
+use super::io::{read_input, system_rng, write_output};
+use super::password::prompt_password;
+use crate::algorithms::cipher::Cipher;
+use crate::algorithms::rsa::{RsaKey, RsaPublicKey};
+use crate::encoding::pem::{decrypt_pem, encode_pem, encrypt_pem, parse_pem, Pem};
+use crate::encoding::pkcs1::{decode_rsa_private_key, decode_rsa_public_key, encode_rsa_private_key};
+use crate::encoding::pkcs8::{
+    decode_private_key_info, decrypt_private_key_info, encode_private_key_info,
+    encrypt_private_key_info, Pbes2Params,
+};
+use crate::encoding::spki::{decode_public_key_info, encode_public_key_info};
+use crate::error::FtSslError;
+use num::BigInt;
+
+/// Key file encodings accepted by `-inform` and `-outform`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyFormat {
+    Pem,
+    Der,
+}
+
+impl KeyFormat {
+    pub fn from_name(name: &str) -> Option<KeyFormat> {
+        match name.to_ascii_uppercase().as_str() {
+            "PEM" => Some(KeyFormat::Pem),
+            "DER" => Some(KeyFormat::Der),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a `-inform`/`-outform` value.
+pub fn parse_format(option: &str, value: &str) -> Result<KeyFormat, FtSslError> {
+    KeyFormat::from_name(value)
+        .ok_or_else(|| FtSslError::Usage(format!("Invalid format \"{}\" for option {}", value, option)))
+}
+
+/// Parses a `-cipher` value.
+pub fn parse_cipher(name: &str) -> Result<Cipher, FtSslError> {
+    Cipher::from_name(name).ok_or_else(|| FtSslError::Usage(format!("Unknown cipher: {}", name)))
+}
+
+fn passphrase(infile: Option<&String>, passin: Option<&str>) -> Result<String, FtSslError> {
+    match passin {
+        Some(password) => Ok(password.to_string()),
+        None => {
+            let source = infile.map(String::as_str).unwrap_or("-");
+            prompt_password(&format!("Enter pass phrase for {}:", source), false)
+        }
+    }
+}
+
+/// Loads a private key in any supported encoding: traditional PKCS#1 (with
+/// or without PEM encryption), PKCS#8 or encrypted PKCS#8. Encrypted keys
+/// are opened with `passin` or an interactive prompt.
+pub fn load_private_key(
+    infile: Option<&String>,
+    format: KeyFormat,
+    passin: Option<&str>,
+) -> Result<RsaKey, FtSslError> {
+    let content = read_input(infile)?;
+    let key = match format {
+        KeyFormat::Pem => parse_pem(&String::from_utf8_lossy(&content)).and_then(|pem| {
+            match pem.label.as_str() {
+                "RSA PRIVATE KEY" if pem.is_encrypted() => {
+                    let password = passphrase(infile, passin)?;
+                    decrypt_pem(&pem, password.as_bytes())
+                        .and_then(|pem| decode_rsa_private_key(&pem.contents))
+                }
+                "RSA PRIVATE KEY" => decode_rsa_private_key(&pem.contents),
+                "PRIVATE KEY" => decode_private_key_info(&pem.contents),
+                "ENCRYPTED PRIVATE KEY" => {
+                    let password = passphrase(infile, passin)?;
+                    decrypt_private_key_info(&pem.contents, password.as_bytes())
+                }
+                label => Err(FtSslError::Pem(format!("unexpected {}", label))),
+            }
+        }),
+        KeyFormat::Der => decode_private_key_info(&content)
+            .or_else(|_| decode_rsa_private_key(&content))
+            .or_else(|_| {
+                let password = passphrase(infile, passin)?;
+                decrypt_private_key_info(&content, password.as_bytes())
+            }),
+    };
+    key.map_err(|err| err.context("unable to load Private Key"))
+}
+
+/// Loads a SubjectPublicKeyInfo or a PKCS#1 RSAPublicKey.
+pub fn load_public_key(infile: Option<&String>, format: KeyFormat) -> Result<RsaPublicKey, FtSslError> {
+    let content = read_input(infile)?;
+    let key = match format {
+        KeyFormat::Pem => parse_pem(&String::from_utf8_lossy(&content)).and_then(|pem| {
+            match pem.label.as_str() {
+                "PUBLIC KEY" => decode_public_key_info(&pem.contents),
+                "RSA PUBLIC KEY" => decode_rsa_public_key(&pem.contents),
+                label => Err(FtSslError::Pem(format!("unexpected {}", label))),
+            }
+        }),
+        KeyFormat::Der => {
+            decode_public_key_info(&content).or_else(|_| decode_rsa_public_key(&content))
+        }
+    };
+    key.map_err(|err| err.context("unable to load Public Key"))
+}
+
+/// Wraps a private key as PKCS#8 `PRIVATE KEY`, the OpenSSL 3 default, or
+/// as PKCS#1 `RSA PRIVATE KEY` when `traditional` is set. Legacy PEM
+/// encryption only applies to the traditional form.
+pub fn private_key_pem(key: &RsaKey, traditional: bool) -> Pem {
+    if traditional {
+        Pem::new("RSA PRIVATE KEY", encode_rsa_private_key(key))
+    } else {
+        Pem::new("PRIVATE KEY", encode_private_key_info(key))
+    }
+}
+
+/// Encrypted counterpart of `private_key_pem`: PBES2 `ENCRYPTED PRIVATE
+/// KEY` by default, legacy PEM encryption when `traditional` is set.
+pub fn encrypt_private_key_pem(
+    key: &RsaKey,
+    traditional: bool,
+    params: &Pbes2Params,
+    password: &[u8],
+) -> Result<Pem, FtSslError> {
+    let mut rng = system_rng()?;
+    if traditional {
+        return encrypt_pem(&private_key_pem(key, true), params.cipher, password, &mut rng)
+            .map_err(|err| err.context("Error writing key"));
+    }
+    encrypt_private_key_info(key, password, params, &mut rng)
+        .map(|der| Pem::new("ENCRYPTED PRIVATE KEY", der))
+        .map_err(|err| err.context("Error writing key"))
+}
+
+/// Writes `pem` as text, or its bare contents for DER. Traditional PEM
+/// encryption lives in the PEM headers and has no DER form. Like OpenSSL,
+/// `pkey` and `genpkey` write unencrypted DER private keys in the
+/// traditional form.
+pub fn write_key(outfile: Option<&String>, format: KeyFormat, pem: &Pem) -> Result<(), FtSslError> {
+    match format {
+        KeyFormat::Pem => write_output(outfile, encode_pem(pem).as_bytes()),
+        KeyFormat::Der if pem.is_encrypted() => Err(FtSslError::Usage(
+            "Error writing key: traditional encryption needs PEM output".to_string(),
+        )),
+        KeyFormat::Der => write_output(outfile, &pem.contents),
+    }
+}
+
+pub fn public_key_pem(key: &RsaPublicKey) -> Pem {
+    Pem::new("PUBLIC KEY", encode_public_key_info(key))
+}
+
+/// Formats one component like OpenSSL: inline when it fits in 64 bits,
+/// otherwise as an indented colon-separated hex dump.
+fn format_component(name: &str, value: &BigInt) -> String {
+    if value.bits() <= 64 {
+        return format!("{}: {} (0x{})\n", name, value, value.to_str_radix(16));
+    }
+    let mut bytes = value.to_bytes_be().1;
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+    let mut text = format!("{}:\n", name);
+    for line in bytes.chunks(15) {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        text.push_str(&format!("    {}:\n", hex.join(":")));
+    }
+    text.truncate(text.len() - 2);
+    text.push('\n');
+    text
+}
+
+pub fn private_key_text(key: &RsaKey) -> String {
+    let mut text = format!("Private-Key: ({} bit, {} primes)\n", key.bits(), key.prime.len());
+    text.push_str(&format_component("modulus", &key.modulus));
+    text.push_str(&format_component("publicExponent", &key.public_exponent));
+    text.push_str(&format_component("privateExponent", &key.private_exponent));
+    text.push_str(&format_component("prime1", &key.prime[0]));
+    text.push_str(&format_component("prime2", &key.prime[1]));
+    text.push_str(&format_component("exponent1", &key.exponent[0]));
+    text.push_str(&format_component("exponent2", &key.exponent[1]));
+    text.push_str(&format_component("coefficient", &key.coefficient[0]));
+    for i in 2..key.prime.len() {
+        text.push_str(&format_component(&format!("prime{}", i + 1), &key.prime[i]));
+        text.push_str(&format_component(&format!("exponent{}", i + 1), &key.exponent[i]));
+        text.push_str(&format_component(&format!("coefficient{}", i + 1), &key.coefficient[i - 1]));
+    }
+    text
+}
+
+pub fn public_key_text(key: &RsaPublicKey) -> String {
+    let mut text = format!("Public-Key: ({} bit)\n", key.bits());
+    text.push_str(&format_component("Modulus", &key.modulus));
+    text.push_str(&format_component("Exponent", &key.public_exponent));
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::commands::rsa::rsa_command;
+
+    #[test]
+    fn test_malformed_keys_are_errors() {
+        let dir = std::env::temp_dir().join(format!("ft_ssl_key_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let load = |name: &str, content: &[u8], format: KeyFormat| {
+            let path = dir.join(name).to_str().unwrap().to_string();
+            std::fs::write(&path, content).unwrap();
+            load_private_key(Some(&path), format, Some("secret"))
+        };
+
+        let err = load("empty.pem", b"", KeyFormat::Pem).unwrap_err();
+        assert!(matches!(err, FtSslError::Pem(_)));
+        assert_eq!(err.to_string(), "unable to load Private Key: no start line");
+        let truncated = encode_pem(&Pem::new("RSA PRIVATE KEY", vec![0x30, 0x82, 0x01]));
+        assert!(matches!(
+            load("truncated.pem", truncated.as_bytes(), KeyFormat::Pem),
+            Err(FtSslError::Asn1Parse(_))
+        ));
+        assert!(load("garbage.der", b"\x30\x03\x02\x01", KeyFormat::Der).is_err());
+        assert!(matches!(
+            load_private_key(Some(&"/nonexistent/key.pem".to_string()), KeyFormat::Pem, None),
+            Err(FtSslError::Io(_))
+        ));
+
+        let args = ["-in", dir.join("truncated.pem").to_str().unwrap(), "-noout"].map(String::from);
+        assert!(matches!(rsa_command(&args), Err(FtSslError::Asn1Parse(_))));
+        assert!(matches!(rsa_command(&["-bogus".to_string()]), Err(FtSslError::Usage(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
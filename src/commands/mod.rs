@@ -1,2 +1,15 @@
+pub mod enc;
+pub mod genpkey;
 pub mod genrsa;
+pub mod io;
+pub mod kdf;
+pub mod key;
+pub mod options;
+pub mod password;
+pub mod pkey;
+pub mod pkeyutl;
+pub mod prime;
+pub mod rand;
 pub mod rsa;
+pub mod registry;
+pub mod rsautl;
@@ -0,0 +1,207 @@
+use super::key::KeyFormat;
+use super::password::is_password_source;
+use super::registry::{OptionDef, ValueType};
+use crate::error::FtSslError;
+
+/// An option's value, typed by the `ValueType` of its table row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Flag,
+    File(String),
+    Format(KeyFormat),
+    /// A pass phrase source, not yet resolved; see `get_passwords`.
+    Pass(String),
+    Int(u64),
+    Str(String),
+}
+
+/// A command line split by an option table.
+#[derive(Debug, Default)]
+pub struct ParsedArgs {
+    /// Options in command-line order, by table name. An option matched by
+    /// the table's `*` row comes back as `("*", Value::Str(name))`.
+    pub options: Vec<(&'static str, Value)>,
+    pub parameters: Vec<String>,
+}
+
+/// Splits `args` the way OpenSSL's option parser does: options come first,
+/// as `-name`, `-name value` or `-name=value`; the first argument that is
+/// not an option, and everything after `--`, are parameters.
+pub fn parse_args(command: &str, table: &'static [OptionDef], args: &[String]) -> Result<ParsedArgs, FtSslError> {
+    let mut parsed = ParsedArgs::default();
+    let mut i = 0;
+
+    while i < args.len() {
+        let arg = &args[i];
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        let name = match arg.strip_prefix('-') {
+            Some(name) if !name.is_empty() => name,
+            _ => {
+                i -= 1;
+                break;
+            }
+        };
+        let (name, inline) = match name.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (name, None),
+        };
+        let row = table
+            .iter()
+            .find(|option| option.name == name && option.name != "*" && option.value != ValueType::Parameter);
+        let option = match row {
+            Some(option) => option,
+            None if table.iter().any(|option| option.name == "*") && inline.is_none() => {
+                parsed.options.push(("*", Value::Str(name.to_string())));
+                continue;
+            }
+            None => return Err(unknown_option(command, arg)),
+        };
+        if option.value == ValueType::Flag {
+            if inline.is_some() {
+                return Err(usage_error(command, &format!("Option -{} does not take a value", name)));
+            }
+            parsed.options.push((option.name, Value::Flag));
+            continue;
+        }
+        let value = match inline {
+            Some(value) => value,
+            None if i < args.len() => {
+                i += 1;
+                args[i - 1].clone()
+            }
+            None => return Err(usage_error(command, &format!("Option -{} needs a value", name))),
+        };
+        parsed.options.push((option.name, parse_value(command, option, value)?));
+    }
+
+    parsed.parameters = args[i..].to_vec();
+    let takes_parameters = table.iter().any(|option| option.value == ValueType::Parameter);
+    if !parsed.parameters.is_empty() && !takes_parameters {
+        return Err(extra_arguments(command));
+    }
+    Ok(parsed)
+}
+
+fn parse_value(command: &str, option: &OptionDef, value: String) -> Result<Value, FtSslError> {
+    let invalid = |what: &str| usage_error(command, &format!("Invalid {} \"{}\" for option -{}", what, value, option.name));
+    match option.value {
+        ValueType::InFile | ValueType::OutFile => Ok(Value::File(value)),
+        ValueType::Format => KeyFormat::from_name(&value).map(Value::Format).ok_or_else(|| invalid("format")),
+        ValueType::Pass if is_password_source(&value) => Ok(Value::Pass(value)),
+        ValueType::Pass => Err(invalid("pass phrase source")),
+        ValueType::Int => value.parse().map(Value::Int).map_err(|_| invalid("number")),
+        ValueType::PositiveInt => match value.parse() {
+            Ok(number) if number > 0 => Ok(Value::Int(number)),
+            _ => Err(invalid("positive number")),
+        },
+        ValueType::Str => Ok(Value::Str(value)),
+        ValueType::Flag | ValueType::Parameter => unreachable!("-{} takes no value", option.name),
+    }
+}
+
+/// A usage error, followed by OpenSSL's pointer to `-help`.
+pub fn usage_error(command: &str, message: &str) -> FtSslError {
+    FtSslError::Usage(format!("{}: {}\n{}: Use -help for summary.", command, message, command))
+}
+
+pub fn unknown_option(command: &str, arg: &str) -> FtSslError {
+    usage_error(command, &format!("Unknown option: {}", arg))
+}
+
+pub fn extra_arguments(command: &str) -> FtSslError {
+    usage_error(command, "Extra arguments given")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TABLE: &[OptionDef] = &[
+        OptionDef::new("in", ValueType::InFile, "Input file"),
+        OptionDef::new("inform", ValueType::Format, "Input format"),
+        OptionDef::new("passin", ValueType::Pass, "Input pass phrase source"),
+        OptionDef::new("iter", ValueType::PositiveInt, "Iteration count"),
+        OptionDef::new("skip", ValueType::Int, "Bytes to skip"),
+        OptionDef::flag("text", "Print text"),
+        OptionDef::parameter("file", "Files"),
+    ];
+    const NO_PARAMETERS: &[OptionDef] = &[OptionDef::flag("text", "Print text")];
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn error(table: &'static [OptionDef], arg_list: &[&str]) -> String {
+        match parse_args("test", table, &args(arg_list)) {
+            Err(FtSslError::Usage(reason)) => reason,
+            _ => panic!("expected a usage error for {:?}", arg_list),
+        }
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let parsed = parse_args(
+            "test",
+            TABLE,
+            &args(&["-in", "key.pem", "-inform=der", "-passin", "pass:x", "-iter", "7", "-skip=0", "-text"]),
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.options,
+            vec![
+                ("in", Value::File("key.pem".to_string())),
+                ("inform", Value::Format(KeyFormat::Der)),
+                ("passin", Value::Pass("pass:x".to_string())),
+                ("iter", Value::Int(7)),
+                ("skip", Value::Int(0)),
+                ("text", Value::Flag),
+            ]
+        );
+        assert!(parsed.parameters.is_empty());
+    }
+
+    #[test]
+    fn test_parameters_end_the_options() {
+        let parsed = parse_args("test", TABLE, &args(&["-text", "a", "-iter", "1"])).unwrap();
+        assert_eq!(parsed.options, vec![("text", Value::Flag)]);
+        assert_eq!(parsed.parameters, args(&["a", "-iter", "1"]));
+
+        let parsed = parse_args("test", TABLE, &args(&["-text", "--", "-text"])).unwrap();
+        assert_eq!(parsed.options, vec![("text", Value::Flag)]);
+        assert_eq!(parsed.parameters, args(&["-text"]));
+
+        let parsed = parse_args("test", TABLE, &args(&["-", "-text"])).unwrap();
+        assert_eq!(parsed.parameters, args(&["-", "-text"]));
+    }
+
+    #[test]
+    fn test_trailing_flag_is_kept() {
+        let parsed = parse_args("test", TABLE, &args(&["-in", "key.pem", "-text"])).unwrap();
+        assert_eq!(parsed.options.last(), Some(&("text", Value::Flag)));
+    }
+
+    #[test]
+    fn test_wildcard_row() {
+        const CIPHERS: &[OptionDef] = &[OptionDef::flag("*", "Any cipher")];
+        let parsed = parse_args("test", CIPHERS, &args(&["-des3"])).unwrap();
+        assert_eq!(parsed.options, vec![("*", Value::Str("des3".to_string()))]);
+        assert!(error(CIPHERS, &["-des3=x"]).starts_with("test: Unknown option: -des3=x\n"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error(TABLE, &["-bogus"]), "test: Unknown option: -bogus\ntest: Use -help for summary.");
+        assert!(error(TABLE, &["-in"]).starts_with("test: Option -in needs a value\n"));
+        assert!(error(TABLE, &["-text=yes"]).starts_with("test: Option -text does not take a value\n"));
+        assert!(error(TABLE, &["-inform", "xml"]).starts_with("test: Invalid format \"xml\" for option -inform\n"));
+        assert!(error(TABLE, &["-skip", "-1"]).starts_with("test: Invalid number \"-1\" for option -skip\n"));
+        assert!(error(TABLE, &["-iter", "0"]).starts_with("test: Invalid positive number \"0\" for option -iter\n"));
+        assert!(error(TABLE, &["-passin", "secret"])
+            .starts_with("test: Invalid pass phrase source \"secret\" for option -passin\n"));
+        assert!(error(TABLE, &["-file"]).starts_with("test: Unknown option: -file\n"));
+        assert!(error(NO_PARAMETERS, &["extra"]).starts_with("test: Extra arguments given\n"));
+    }
+}
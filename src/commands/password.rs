@@ -0,0 +1,181 @@
+use crate::error::FtSslError;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::io::{AsRawFd, BorrowedFd};
+
+/// Prompts on the controlling terminal and reads one line back with echo
+/// turned off. Fails instead of blocking when there is no terminal.
+pub fn read_password(prompt: &str) -> Result<String, FtSslError> {
+    let bad_read = || FtSslError::BadPassword("bad password read".to_string());
+    let tty = File::options().read(true).write(true).open("/dev/tty").map_err(|_| bad_read())?;
+    let mut writer = &tty;
+    let _ = write!(writer, "{}", prompt);
+    let _ = writer.flush();
+
+    let fd = tty.as_raw_fd();
+    let saved = disable_echo(fd);
+    let mut line = String::new();
+    let result = BufReader::new(&tty).read_line(&mut line);
+    if let Some(saved) = saved {
+        // SAFETY: restores the attributes read from the same open descriptor.
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &saved) };
+    }
+    let _ = writeln!(writer);
+
+    if result.is_err() {
+        return Err(bad_read());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn disable_echo(fd: i32) -> Option<libc::termios> {
+    // SAFETY: termios is plain old data and `fd` is an open terminal.
+    unsafe {
+        let mut attributes: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut attributes) != 0 {
+            return None;
+        }
+        let saved = attributes;
+        attributes.c_lflag &= !libc::ECHO;
+        libc::tcsetattr(fd, libc::TCSANOW, &attributes);
+        Some(saved)
+    }
+}
+
+/// Like `read_password`, asking a second time when `verify` is set.
+pub fn prompt_password(prompt: &str, verify: bool) -> Result<String, FtSslError> {
+    let password = read_password(prompt)?;
+    if verify && read_password(&format!("Verifying - {}", prompt))? != password {
+        return Err(FtSslError::BadPassword("Verify failure\nbad password read".to_string()));
+    }
+    Ok(password)
+}
+
+/// Resolves an OpenSSL pass phrase argument: `pass:secret`, `env:VAR`,
+/// `file:path`, `fd:N` or `stdin`. Sources that read from a stream use
+/// their first line.
+pub fn get_password(source: &str) -> Result<String, FtSslError> {
+    get_passwords(Some(source), None).map(|(password, _)| password.unwrap())
+}
+
+/// Resolves `-passin` and `-passout` together. When both name the same
+/// file, descriptor or stdin, the first line is the input pass phrase and
+/// the second line the output one, as OpenSSL does.
+pub fn get_passwords(
+    passin: Option<&str>,
+    passout: Option<&str>,
+) -> Result<(Option<String>, Option<String>), FtSslError> {
+    let shared = match (passin, passout) {
+        (Some(a), Some(b)) => a == b && !a.starts_with("pass:") && !a.starts_with("env:"),
+        _ => false,
+    };
+    if shared {
+        let mut reader = open_source(passin.unwrap())?;
+        let first = read_line(&mut reader, passin.unwrap())?;
+        let second = read_line(&mut reader, passout.unwrap())?;
+        return Ok((Some(first), Some(second)));
+    }
+
+    let resolve = |source: Option<&str>| -> Result<Option<String>, FtSslError> {
+        match source {
+            None => Ok(None),
+            Some(source) => resolve_one(source).map(Some),
+        }
+    };
+    Ok((resolve(passin)?, resolve(passout)?))
+}
+
+/// Whether `source` has one of the forms `get_passwords` resolves.
+pub fn is_password_source(source: &str) -> bool {
+    source == "stdin" || ["pass:", "env:", "file:", "fd:"].iter().any(|prefix| source.starts_with(prefix))
+}
+
+fn resolve_one(source: &str) -> Result<String, FtSslError> {
+    if let Some(password) = source.strip_prefix("pass:") {
+        return Ok(password.to_string());
+    }
+    if let Some(name) = source.strip_prefix("env:") {
+        return std::env::var(name)
+            .map_err(|_| FtSslError::BadPassword(format!("No environment variable {}", name)));
+    }
+    let mut reader = open_source(source)?;
+    read_line(&mut reader, source)
+}
+
+fn open_source(source: &str) -> Result<Box<dyn BufRead>, FtSslError> {
+    if let Some(path) = source.strip_prefix("file:") {
+        let file = File::open(path)
+            .map_err(|err| FtSslError::BadPassword(format!("Can't open file {}: {}", path, err)))?;
+        return Ok(Box::new(BufReader::new(file)));
+    }
+    if let Some(number) = source.strip_prefix("fd:") {
+        let fd: i32 = number
+            .parse()
+            .map_err(|_| FtSslError::BadPassword(format!("Can't access file descriptor {}", number)))?;
+        if fd < 0 {
+            return Err(FtSslError::Usage(format!("Invalid file descriptor {}", fd)));
+        }
+        // SAFETY: the descriptor is only borrowed long enough to duplicate
+        // it, so the caller's stays open like OpenSSL's BIO_NOCLOSE; a
+        // descriptor that is not open makes the dup fail with EBADF.
+        let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+        let file = borrowed
+            .try_clone_to_owned()
+            .map_err(|err| FtSslError::BadPassword(format!("Can't access file descriptor {}: {}", fd, err)))?;
+        return Ok(Box::new(BufReader::new(File::from(file))));
+    }
+    if source == "stdin" {
+        return Ok(Box::new(BufReader::new(io::stdin())));
+    }
+    Err(FtSslError::BadPassword(format!("Invalid password argument \"{}\"", source)))
+}
+
+fn read_line(reader: &mut Box<dyn BufRead>, source: &str) -> Result<String, FtSslError> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) | Err(_) => Err(FtSslError::BadPassword(format!("Error reading password from {}", source))),
+        Ok(_) => Ok(line.trim_end_matches(['\r', '\n']).to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pass_and_env_sources() {
+        std::env::set_var("FT_SSL_TEST_PASSWORD", "from env");
+
+        assert_eq!(get_password("pass:secret").unwrap(), "secret");
+        assert_eq!(get_password("pass:").unwrap(), "");
+        assert_eq!(get_password("env:FT_SSL_TEST_PASSWORD").unwrap(), "from env");
+        assert!(get_password("env:FT_SSL_TEST_UNSET_VARIABLE").is_err());
+        assert!(get_password("secret").is_err());
+    }
+
+    #[test]
+    fn test_file_source_reads_one_line_per_direction() {
+        let path = std::env::temp_dir().join(format!("ft_ssl_pass_{}", std::process::id()));
+        std::fs::write(&path, "first\nsecond\n").unwrap();
+        let source = format!("file:{}", path.display());
+
+        assert_eq!(get_password(&source).unwrap(), "first");
+        assert_eq!(
+            get_passwords(Some(&source), Some(&source)).unwrap(),
+            (Some("first".to_string()), Some("second".to_string()))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_fd_source_leaves_the_descriptor_open() {
+        let path = std::env::temp_dir().join(format!("ft_ssl_pass_fd_{}", std::process::id()));
+        std::fs::write(&path, "first\n").unwrap();
+        let file = File::open(&path).unwrap();
+
+        assert_eq!(get_password(&format!("fd:{}", file.as_raw_fd())).unwrap(), "first");
+        assert!(file.metadata().is_ok());
+        assert!(matches!(get_password("fd:-1"), Err(FtSslError::Usage(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+use super::key::{
+    encrypt_private_key_pem, load_private_key, load_public_key, parse_cipher,
+    private_key_pem, private_key_text, public_key_pem, public_key_text, write_key, KeyFormat,
+};
+use super::options::{parse_args, unknown_option, Value};
+use super::password::{get_passwords, prompt_password};
+use crate::algorithms::cipher::Cipher;
+use crate::encoding::pkcs8::Pbes2Params;
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+
+pub struct PkeyOption {
+    inform: KeyFormat,
+    outform: KeyFormat,
+    infile: Option<String>,
+    outfile: Option<String>,
+    passin: Option<String>,
+    passout: Option<String>,
+    cipher: Option<Cipher>,
+    pubin: bool,
+    pubout: bool,
+    text: bool,
+    text_pub: bool,
+    noout: bool,
+    traditional: bool,
+    iterations: u64,
+    salt_len: usize,
+}
+
+impl Default for PkeyOption {
+    fn default() -> Self {
+        PkeyOption {
+            inform: KeyFormat::Pem,
+            outform: KeyFormat::Pem,
+            infile: None,
+            outfile: None,
+            passin: None,
+            passout: None,
+            cipher: None,
+            pubin: false,
+            pubout: false,
+            text: false,
+            text_pub: false,
+            noout: false,
+            traditional: false,
+            iterations: Pbes2Params::default().iterations,
+            salt_len: Pbes2Params::default().salt_len,
+        }
+    }
+}
+
+const PKEY_OPTIONS: &[OptionDef] = &[
+    OptionDef::new("in", ValueType::InFile, "Input key"),
+    OptionDef::new("inform", ValueType::Format, "Input format (DER or PEM)"),
+    OptionDef::new("passin", ValueType::Pass, "Input file pass phrase source"),
+    OptionDef::flag("pubin", "Read only public components from key input"),
+    OptionDef::new("out", ValueType::OutFile, "Output file for encoded and/or text output"),
+    OptionDef::new("outform", ValueType::Format, "Output encoding format (DER or PEM)"),
+    OptionDef::new("passout", ValueType::Pass, "Output PEM file pass phrase source"),
+    OptionDef::new("cipher", ValueType::Str, "Cipher to be used for encryption"),
+    OptionDef::flag("*", "Same as -cipher with the given CBC cipher"),
+    OptionDef::new("iter", ValueType::PositiveInt, "Specify the iteration count for PBKDF2"),
+    OptionDef::new("saltlen", ValueType::PositiveInt, "Specify the PBKDF2 salt length in bytes"),
+    OptionDef::flag("pubout", "Restrict encoded output to public components"),
+    OptionDef::flag("traditional", "Use traditional format for private key PEM output"),
+    OptionDef::flag("noout", "Do not output the key in encoded form"),
+    OptionDef::flag("text", "Output key components in plaintext"),
+    OptionDef::flag("text_pub", "Output only public key components in text form"),
+];
+
+pub struct Pkey;
+
+impl Command for Pkey {
+    fn name(&self) -> &str {
+        "pkey"
+    }
+
+    fn usage(&self) -> &str {
+        "[options]"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        PKEY_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        pkey_command(args)
+    }
+}
+
+/// Converts a key between PKCS#1, PKCS#8 and SPKI in PEM or DER. Unlike
+/// `rsa`, the default private key output is PKCS#8.
+pub fn pkey_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_pkey_options(args)?;
+    let (passin, passout) = get_passwords(option.passin.as_deref(), option.passout.as_deref())
+        .map_err(|err| err.context("Error getting passwords"))?;
+
+    if option.pubin {
+        let key = load_public_key(option.infile.as_ref(), option.inform)?;
+        if !option.noout {
+            write_key(option.outfile.as_ref(), option.outform, &public_key_pem(&key))?;
+        }
+        if option.text || option.text_pub {
+            print!("{}", public_key_text(&key));
+        }
+        return Ok(());
+    }
+
+    let key = load_private_key(option.infile.as_ref(), option.inform, passin.as_deref())?;
+    if !option.noout {
+        let pem = if option.pubout {
+            public_key_pem(&key.public_key())
+        } else if let Some(cipher) = option.cipher {
+            let password = match passout {
+                Some(password) => password,
+                None => prompt_password("Enter PEM pass phrase:", true)?,
+            };
+            let params = Pbes2Params {
+                cipher,
+                iterations: option.iterations,
+                salt_len: option.salt_len,
+            };
+            encrypt_private_key_pem(&key, option.traditional, &params, password.as_bytes())?
+        } else {
+            private_key_pem(&key, option.traditional || option.outform == KeyFormat::Der)
+        };
+        write_key(option.outfile.as_ref(), option.outform, &pem)?;
+    }
+    if option.text_pub || (option.text && option.pubout) {
+        print!("{}", public_key_text(&key.public_key()));
+    } else if option.text {
+        print!("{}", private_key_text(&key));
+    }
+    Ok(())
+}
+
+pub fn parse_pkey_options(args: &[String]) -> Result<PkeyOption, FtSslError> {
+    let mut option = PkeyOption::default();
+
+    for (name, value) in parse_args("pkey", PKEY_OPTIONS, args)?.options {
+        match (name, value) {
+            ("pubin", _) => {
+                option.pubin = true;
+                option.pubout = true;
+            }
+            ("pubout", _) => option.pubout = true,
+            ("text", _) => option.text = true,
+            ("text_pub", _) => option.text_pub = true,
+            ("noout", _) => option.noout = true,
+            ("traditional", _) => option.traditional = true,
+            ("iter", Value::Int(iterations)) => option.iterations = iterations,
+            ("saltlen", Value::Int(salt_len)) => option.salt_len = salt_len as usize,
+            ("in", Value::File(file)) => option.infile = Some(file),
+            ("inform", Value::Format(format)) => option.inform = format,
+            ("out", Value::File(file)) => option.outfile = Some(file),
+            ("outform", Value::Format(format)) => option.outform = format,
+            ("passin", Value::Pass(source)) => option.passin = Some(source),
+            ("passout", Value::Pass(source)) => option.passout = Some(source),
+            ("cipher", Value::Str(cipher)) => option.cipher = Some(parse_cipher(&cipher)?),
+            ("*", Value::Str(cipher)) => match Cipher::from_name(&cipher).filter(Cipher::is_cbc) {
+                Some(cipher) => option.cipher = Some(cipher),
+                None => return Err(unknown_option("pkey", &format!("-{}", cipher))),
+            },
+            (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+        }
+    }
+    Ok(option)
+}
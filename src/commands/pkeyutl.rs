@@ -0,0 +1,208 @@
+use super::io::{read_input, system_rng, write_output};
+use super::key::{load_private_key, load_public_key, KeyFormat};
+use super::options::{parse_args, Value};
+use super::password::get_password;
+use crate::algorithms::digest::Digest;
+use crate::algorithms::rsa_padding::{
+    decrypt, encrypt, sign, verify, verify_recover, PaddingMode, PaddingParams, SaltLength,
+};
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+use super::rsautl::RSAUTL_OPTIONS;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    VerifyRecover,
+}
+
+pub struct PkeyutlOption {
+    operation: Operation,
+    infile: Option<String>,
+    outfile: Option<String>,
+    inkey: Option<String>,
+    keyform: KeyFormat,
+    passin: Option<String>,
+    pubin: bool,
+    sigfile: Option<String>,
+    padding: PaddingParams,
+}
+
+impl Default for PkeyutlOption {
+    fn default() -> Self {
+        PkeyutlOption {
+            operation: Operation::Sign,
+            infile: None,
+            outfile: None,
+            inkey: None,
+            keyform: KeyFormat::Pem,
+            passin: None,
+            pubin: false,
+            sigfile: None,
+            padding: PaddingParams::default(),
+        }
+    }
+}
+
+const PKEYUTL_OPTIONS: &[OptionDef] = &[
+    OptionDef::flag("encrypt", "Encrypt input data with public key"),
+    OptionDef::flag("decrypt", "Decrypt input data with private key"),
+    OptionDef::flag("sign", "Sign input data with private key"),
+    OptionDef::flag("verify", "Verify with public key"),
+    OptionDef::flag("verifyrecover", "Verify RSA signature, recovering original signature input data"),
+    OptionDef::new("in", ValueType::InFile, "Input file - default stdin"),
+    OptionDef::new("out", ValueType::OutFile, "Output file - default stdout"),
+    OptionDef::new("inkey", ValueType::InFile, "Input key"),
+    OptionDef::new("keyform", ValueType::Format, "Key format (DER or PEM)"),
+    OptionDef::new("passin", ValueType::Pass, "Input file pass phrase source"),
+    OptionDef::flag("pubin", "Input key is a public key"),
+    OptionDef::new("sigfile", ValueType::InFile, "Signature file (verify operation only)"),
+    OptionDef::new("pkeyopt", ValueType::Str, "Public key options as opt:value"),
+];
+
+pub struct Pkeyutl;
+
+impl Command for Pkeyutl {
+    fn name(&self) -> &str {
+        "pkeyutl"
+    }
+
+    fn usage(&self) -> &str {
+        "[options]"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        PKEYUTL_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        pkeyutl_command(args)
+    }
+}
+
+pub fn pkeyutl_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_pkeyutl_options(args)?;
+    run(&option, "pkeyutl")
+}
+
+/// Runs one RSA operation; shared with `rsautl`, which only differs in how
+/// its options are spelled.
+pub fn run(option: &PkeyutlOption, command: &str) -> Result<(), FtSslError> {
+    if option.inkey.is_none() {
+        return Err(FtSslError::Usage(format!("{}: no private key given (-inkey parameter)", command)));
+    }
+    let passin = match option.passin.as_deref().map(get_password) {
+        Some(password) => Some(password.map_err(|err| err.context("Error getting password"))?),
+        None => None,
+    };
+    let needs_private = matches!(option.operation, Operation::Decrypt | Operation::Sign);
+    if option.pubin && needs_private {
+        return Err(FtSslError::Usage(format!("{}: a private key is needed for this operation", command)));
+    }
+    let private = match option.pubin {
+        false => Some(load_private_key(option.inkey.as_ref(), option.keyform, passin.as_deref())?),
+        true => None,
+    };
+    let public = match &private {
+        Some(key) => key.public_key(),
+        None => load_public_key(option.inkey.as_ref(), option.keyform)?,
+    };
+
+    let input = read_input(option.infile.as_ref())?;
+    let params = &option.padding;
+    let result = match option.operation {
+        Operation::Encrypt => encrypt(&public, params, &input, &mut system_rng()?),
+        Operation::Decrypt => decrypt(private.as_ref().unwrap(), params, &input),
+        Operation::Sign => sign(private.as_ref().unwrap(), params, &input, &mut system_rng()?),
+        Operation::VerifyRecover => verify_recover(&public, params, &input),
+        Operation::Verify => {
+            let signature = read_input(option.sigfile.as_ref())?;
+            match verify(&public, params, &input, &signature) {
+                Ok(true) => {
+                    println!("Signature Verified Successfully");
+                    return Ok(());
+                }
+                Ok(false) => return Err(FtSslError::VerificationFailure),
+                Err(reason) => Err(reason),
+            }
+        }
+    };
+    let output = result.map_err(|reason| FtSslError::Operation(format!("Public Key operation error: {}", reason)))?;
+    write_output(option.outfile.as_ref(), &output)
+}
+
+pub fn parse_pkeyutl_options(args: &[String]) -> Result<PkeyutlOption, FtSslError> {
+    let mut option = PkeyutlOption::default();
+
+    for (name, value) in parse_args("pkeyutl", PKEYUTL_OPTIONS, args)?.options {
+        match (name, value) {
+            ("verify", _) => option.operation = Operation::Verify,
+            ("verifyrecover", _) => option.operation = Operation::VerifyRecover,
+            ("sigfile", Value::File(file)) => option.sigfile = Some(file),
+            ("pkeyopt", Value::Str(pkeyopt)) => parse_pkeyopt(&mut option.padding, &pkeyopt)?,
+            (name, value) => set_shared_option(&mut option, name, value),
+        }
+    }
+    if option.operation == Operation::Verify && option.sigfile.is_none() {
+        return Err(FtSslError::Usage("No signature file specified for verify".to_string()));
+    }
+    Ok(option)
+}
+
+/// Options spelled the same way by `pkeyutl` and `rsautl`.
+fn set_shared_option(option: &mut PkeyutlOption, name: &str, value: Value) {
+    match (name, value) {
+        ("encrypt", _) => option.operation = Operation::Encrypt,
+        ("decrypt", _) => option.operation = Operation::Decrypt,
+        ("sign", _) => option.operation = Operation::Sign,
+        ("pubin", _) => option.pubin = true,
+        ("in", Value::File(file)) => option.infile = Some(file),
+        ("out", Value::File(file)) => option.outfile = Some(file),
+        ("inkey", Value::File(file)) => option.inkey = Some(file),
+        ("keyform", Value::Format(format)) => option.keyform = format,
+        ("passin", Value::Pass(source)) => option.passin = Some(source),
+        (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+    }
+}
+
+fn parse_pkeyopt(padding: &mut PaddingParams, pkeyopt: &str) -> Result<(), FtSslError> {
+    let parsed = match pkeyopt.split_once(':') {
+        Some(("rsa_padding_mode", mode)) => {
+            PaddingMode::from_name(mode).map(|mode| padding.mode = mode)
+        }
+        Some(("digest", name)) => Digest::from_name(name).map(|d| padding.digest = Some(d)),
+        Some(("rsa_oaep_md", name)) => Digest::from_name(name).map(|d| padding.oaep_digest = d),
+        Some(("rsa_mgf1_md", name)) => Digest::from_name(name).map(|d| padding.mgf1_digest = Some(d)),
+        Some(("rsa_pss_saltlen", value)) => {
+            let salt_len = match value {
+                "digest" => Some(SaltLength::Digest),
+                "max" => Some(SaltLength::Max),
+                "auto" => Some(SaltLength::Auto),
+                bytes => bytes.parse().ok().map(SaltLength::Bytes),
+            };
+            salt_len.map(|salt_len| padding.salt_len = salt_len)
+        }
+        _ => None,
+    };
+    parsed.ok_or_else(|| FtSslError::Usage(format!("parameter setting error: {}", pkeyopt)))
+}
+
+/// Builds the options from `rsautl` spellings: `-pkcs`, `-oaep`, `-raw`,
+/// and `-verify` meaning signature recovery.
+pub fn parse_rsautl_options(args: &[String]) -> Result<PkeyutlOption, FtSslError> {
+    let mut option = PkeyutlOption::default();
+
+    for (name, value) in parse_args("rsautl", RSAUTL_OPTIONS, args)?.options {
+        match (name, value) {
+            ("pkcs", _) => option.padding.mode = PaddingMode::Pkcs1,
+            ("oaep", _) => option.padding.mode = PaddingMode::Oaep,
+            ("raw", _) => option.padding.mode = PaddingMode::None,
+            ("verify", _) => option.operation = Operation::VerifyRecover,
+            (name, value) => set_shared_option(&mut option, name, value),
+        }
+    }
+    Ok(option)
+}
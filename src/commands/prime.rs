@@ -0,0 +1,127 @@
+use super::io::system_rng;
+use super::options::{parse_args, Value};
+use crate::algorithms::primality::{generate_probable_prime, generate_safe_prime, PrimalityTest};
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+use num::BigInt;
+
+#[derive(Default)]
+pub struct PrimeOption {
+    hex: bool,
+    test: PrimalityTest,
+    generate: bool,
+    bits: Option<usize>,
+    safe: bool,
+    numbers: Vec<String>,
+}
+
+const PRIME_OPTIONS: &[OptionDef] = &[
+    OptionDef::flag("hex", "Enter and print numbers in hex"),
+    OptionDef::flag("generate", "Generate a prime"),
+    OptionDef::new("bits", ValueType::PositiveInt, "Size of number in bits"),
+    OptionDef::flag("safe", "When used with -generate, generate a safe prime"),
+    OptionDef::new("checks", ValueType::PositiveInt, "Use this many Miller-Rabin rounds instead of Baillie-PSW"),
+    OptionDef::parameter("number", "Number(s) to check for primality if not generating"),
+];
+
+pub struct Prime;
+
+impl Command for Prime {
+    fn name(&self) -> &str {
+        "prime"
+    }
+
+    fn usage(&self) -> &str {
+        "[options] [number...]"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        PRIME_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        prime_command(args)
+    }
+}
+
+/// Tests each number for primality, with Baillie-PSW or, given `-checks N`,
+/// N rounds of Miller-Rabin. With `-generate` prints a random prime of
+/// `-bits` bits instead.
+pub fn prime_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_prime_options(args)?;
+    let mut rng = system_rng()?;
+
+    if option.generate {
+        let prime = match option.bits {
+            Some(bits) if option.safe => generate_safe_prime(bits, &mut rng).ok(),
+            Some(bits) if bits >= 2 => Some(generate_probable_prime(bits, &mut rng)),
+            Some(_) => None,
+            None => return Err(FtSslError::Usage("Specify the number of bits.".to_string())),
+        };
+        match prime {
+            Some(prime) if option.hex => println!("{}", prime.to_str_radix(16).to_uppercase()),
+            Some(prime) => println!("{}", prime),
+            None => return Err(FtSslError::Operation("Failed to generate prime.".to_string())),
+        }
+        return Ok(());
+    }
+
+    if option.numbers.is_empty() {
+        return Err(FtSslError::Usage("Missing number (s) to check".to_string()));
+    }
+    let radix = if option.hex { 16 } else { 10 };
+    for arg in &option.numbers {
+        let number = parse_number(arg, radix)
+            .ok_or_else(|| FtSslError::Usage(format!("Failed to process value ({})", arg)))?;
+        let verdict = if option.test.is_prime(&number, &mut rng) {
+            "is prime"
+        } else {
+            "is not prime"
+        };
+        println!("{} ({}) {}", number.to_str_radix(16).to_uppercase(), arg, verdict);
+    }
+    Ok(())
+}
+
+/// A non-negative integer in the given radix, without sign or prefix.
+fn parse_number(arg: &str, radix: u32) -> Option<BigInt> {
+    if arg.is_empty() || !arg.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    BigInt::parse_bytes(arg.as_bytes(), radix)
+}
+
+pub fn parse_prime_options(args: &[String]) -> Result<PrimeOption, FtSslError> {
+    let mut option = PrimeOption::default();
+    let parsed = parse_args("prime", PRIME_OPTIONS, args)?;
+
+    for (name, value) in parsed.options {
+        match (name, value) {
+            ("hex", _) => option.hex = true,
+            ("generate", _) => option.generate = true,
+            ("safe", _) => option.safe = true,
+            ("checks", Value::Int(rounds)) => {
+                option.test = PrimalityTest::MillerRabin { rounds: rounds as usize };
+            }
+            ("bits", Value::Int(bits)) => option.bits = Some(bits as usize),
+            (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+        }
+    }
+    option.numbers = parsed.parameters;
+    Ok(option)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("97", 10), Some(BigInt::from(97)));
+        assert_eq!(parse_number("ff", 16), Some(BigInt::from(255)));
+        assert_eq!(parse_number("ff", 10), None);
+        assert_eq!(parse_number("0x11", 10), None);
+        assert_eq!(parse_number("+5", 10), None);
+        assert_eq!(parse_number("", 10), None);
+    }
+}
@@ -0,0 +1,218 @@
+use super::io::{key_rng, open_output, parse_seed, SEEDED_WARNING};
+use super::options::{extra_arguments, parse_args, Value};
+use crate::algorithms::rng::{CryptoRng, MAX_REQUEST_BYTES};
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+use base64::write::EncoderWriter;
+use std::io::{self, Write};
+
+#[derive(Default)]
+pub struct RandOption {
+    hex: bool,
+    base64: bool,
+    outfile: Option<String>,
+    seed: Option<Vec<u8>>,
+    num: Option<usize>,
+}
+
+const RAND_OPTIONS: &[OptionDef] = &[
+    OptionDef::new("out", ValueType::OutFile, "Output file"),
+    OptionDef::flag("base64", "Base64 encode output"),
+    OptionDef::flag("hex", "Hex encode output"),
+    OptionDef::new("seed", ValueType::Str, "Seed the generator with the given hex string"),
+    OptionDef::new("rand", ValueType::Str, "Seed the generator with the given file(s)"),
+    OptionDef::parameter("num", "Number of bytes to generate"),
+];
+
+pub struct Rand;
+
+impl Command for Rand {
+    fn name(&self) -> &str {
+        "rand"
+    }
+
+    fn usage(&self) -> &str {
+        "[options] num"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        RAND_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        rand_command(args)
+    }
+}
+
+/// Writes `num` bytes from the DRBG, raw or as `-hex`/`-base64` text.
+pub fn rand_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_rand_options(args)?;
+    let num = option
+        .num
+        .ok_or_else(|| FtSslError::Usage("rand: Use -help for summary.".to_string()))?;
+
+    if option.seed.is_some() {
+        eprintln!("{}", SEEDED_WARNING);
+    }
+    let mut rng = key_rng(option.seed.as_deref())?;
+    let mut output = open_output(option.outfile.as_ref())?;
+    let result = if option.hex {
+        write_random(rng.as_mut(), num, |bytes| {
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            output.write_all(hex.as_bytes())
+        })
+        .and_then(|_| output.write_all(b"\n"))
+    } else if option.base64 {
+        let mut encoder = EncoderWriter::new(LineWrap::new(&mut output), base64::STANDARD);
+        write_random(rng.as_mut(), num, |bytes| encoder.write_all(bytes))
+            .and_then(|_| encoder.finish())
+            .and_then(|mut lines| lines.finish())
+    } else {
+        write_random(rng.as_mut(), num, |bytes| output.write_all(bytes))
+    };
+    result
+        .and_then(|_| output.flush())
+        .map_err(|err| FtSslError::Io(format!("error writing output file: {}", err)))
+}
+
+/// Hands `num` random bytes to `write` a chunk at a time, so that large
+/// counts never sit in memory. Chunks are one DRBG request each, which
+/// keeps seeded output the same as a single `fill_bytes` of `num` bytes.
+fn write_random(
+    rng: &mut dyn CryptoRng,
+    num: usize,
+    mut write: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut chunk = vec![0u8; num.min(MAX_REQUEST_BYTES)];
+    let mut remaining = num;
+    while remaining > 0 {
+        let bytes = &mut chunk[..remaining.min(MAX_REQUEST_BYTES)];
+        rng.fill_bytes(bytes);
+        write(bytes)?;
+        remaining -= bytes.len();
+    }
+    Ok(())
+}
+
+/// Breaks base64 text into 64-character lines, as `base64_wrap` does,
+/// however the text arrives.
+struct LineWrap<W: Write> {
+    inner: W,
+    column: usize,
+}
+
+impl<W: Write> LineWrap<W> {
+    fn new(inner: W) -> Self {
+        LineWrap { inner, column: 0 }
+    }
+
+    /// Ends the last line if it is not already ended.
+    fn finish(&mut self) -> io::Result<()> {
+        if self.column > 0 {
+            self.column = 0;
+            self.inner.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for LineWrap<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let (line, tail) = rest.split_at(rest.len().min(64 - self.column));
+            self.inner.write_all(line)?;
+            self.column += line.len();
+            if self.column == 64 {
+                self.column = 0;
+                self.inner.write_all(b"\n")?;
+            }
+            rest = tail;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub fn parse_rand_options(args: &[String]) -> Result<RandOption, FtSslError> {
+    let mut option = RandOption::default();
+    let parsed = parse_args("rand", RAND_OPTIONS, args)?;
+
+    for (name, value) in parsed.options {
+        match (name, value) {
+            ("hex", _) => option.hex = true,
+            ("base64", _) => option.base64 = true,
+            ("out", Value::File(file)) => option.outfile = Some(file),
+            ("seed" | "rand", Value::Str(seed)) => option
+                .seed
+                .get_or_insert_with(Vec::new)
+                .extend(parse_seed(&format!("-{}", name), &seed)?),
+            (name, value) => unreachable!("-{} parsed as {:?}", name, value),
+        }
+    }
+    match parsed.parameters.as_slice() {
+        [] => (),
+        [num] => {
+            let num = parse_count(num)
+                .ok_or_else(|| FtSslError::Usage(format!("rand: Invalid number of bytes: {}", num)))?;
+            option.num = Some(num);
+        }
+        _ => return Err(extra_arguments("rand")),
+    }
+    if option.hex && option.base64 {
+        return Err(FtSslError::Usage("rand: -hex and -base64 are mutually exclusive".to_string()));
+    }
+    Ok(option)
+}
+
+/// A positive byte count, optionally with OpenSSL's K, M, G or T suffix
+/// (powers of 1024).
+fn parse_count(value: &str) -> Option<usize> {
+    let (digits, shift) = match value.char_indices().last()? {
+        (i, 'K') => (&value[..i], 10),
+        (i, 'M') => (&value[..i], 20),
+        (i, 'G') => (&value[..i], 30),
+        (i, 'T') => (&value[..i], 40),
+        _ => (value, 0),
+    };
+    let count: usize = digits.parse().ok()?;
+    count.checked_mul(1 << shift).filter(|&count| count > 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithms::rng::HmacDrbg;
+    use crate::commands::enc::base64_wrap;
+
+    #[test]
+    fn test_chunks_match_one_fill() {
+        let num = 2 * MAX_REQUEST_BYTES + 100;
+        let mut expected = vec![0u8; num];
+        HmacDrbg::from_seed(b"chunks").fill_bytes(&mut expected);
+
+        let mut raw = Vec::new();
+        write_random(&mut HmacDrbg::from_seed(b"chunks"), num, |bytes| raw.write_all(bytes)).unwrap();
+        assert_eq!(raw, expected);
+
+        let mut encoder = EncoderWriter::new(LineWrap::new(Vec::new()), base64::STANDARD);
+        write_random(&mut HmacDrbg::from_seed(b"chunks"), num, |bytes| encoder.write_all(bytes)).unwrap();
+        let mut lines = encoder.finish().unwrap();
+        lines.finish().unwrap();
+        assert_eq!(String::from_utf8(lines.inner).unwrap(), base64_wrap(&expected));
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("16"), Some(16));
+        assert_eq!(parse_count("2K"), Some(2048));
+        assert_eq!(parse_count("1M"), Some(1 << 20));
+        assert_eq!(parse_count("0"), None);
+        assert_eq!(parse_count("-1"), None);
+        assert_eq!(parse_count("K"), None);
+        assert_eq!(parse_count("1k"), None);
+    }
+}
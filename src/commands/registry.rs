@@ -0,0 +1,339 @@
+use super::enc::{CipherCommand, Enc};
+use super::genpkey::Genpkey;
+use super::genrsa::Genrsa;
+use super::kdf::Kdf;
+use super::pkey::Pkey;
+use super::pkeyutl::Pkeyutl;
+use super::prime::Prime;
+use super::rand::Rand;
+use super::rsa::Rsa;
+use super::rsautl::Rsautl;
+use crate::algorithms::cipher::Cipher;
+use crate::error::FtSslError;
+
+/// The section a command is listed under by `ft_ssl help`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandKind {
+    Standard,
+    MessageDigest,
+    Cipher,
+}
+
+/// What an option takes, shown after its name in `-help`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    /// A flag without a value.
+    Flag,
+    InFile,
+    OutFile,
+    /// `PEM` or `DER`.
+    Format,
+    /// A pass phrase source such as `pass:secret` or `file:path`.
+    Pass,
+    /// A non-negative integer.
+    Int,
+    /// An integer greater than zero.
+    PositiveInt,
+    /// Any other string.
+    Str,
+    /// A positional parameter rather than an option.
+    Parameter,
+}
+
+impl ValueType {
+    fn placeholder(&self) -> Option<&'static str> {
+        match self {
+            ValueType::Flag | ValueType::Parameter => None,
+            ValueType::InFile => Some("infile"),
+            ValueType::OutFile => Some("outfile"),
+            ValueType::Format => Some("format"),
+            ValueType::Int => Some("int"),
+            ValueType::PositiveInt => Some("+int"),
+            ValueType::Pass | ValueType::Str => Some("val"),
+        }
+    }
+}
+
+/// One row of a command's option table, named without its leading `-`.
+#[derive(Clone, Copy, Debug)]
+pub struct OptionDef {
+    pub name: &'static str,
+    pub value: ValueType,
+    pub help: &'static str,
+}
+
+impl OptionDef {
+    pub const fn new(name: &'static str, value: ValueType, help: &'static str) -> Self {
+        OptionDef { name, value, help }
+    }
+
+    pub const fn flag(name: &'static str, help: &'static str) -> Self {
+        OptionDef::new(name, ValueType::Flag, help)
+    }
+
+    pub const fn parameter(name: &'static str, help: &'static str) -> Self {
+        OptionDef::new(name, ValueType::Parameter, help)
+    }
+}
+
+/// A subcommand of `ft_ssl`. Implement it and hand it to
+/// `Registry::register` to add a command without touching the binary.
+pub trait Command {
+    fn name(&self) -> &str;
+
+    /// The synopsis after the command name, e.g. `[options] numbits`.
+    fn usage(&self) -> &str;
+
+    /// Options and positional parameters, in the order `-help` lists them.
+    fn options(&self) -> &[OptionDef];
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError>;
+
+    fn kind(&self) -> CommandKind {
+        CommandKind::Standard
+    }
+}
+
+/// `command -help`, laid out like OpenSSL's option summaries.
+pub fn help_text(command: &dyn Command) -> String {
+    let mut text = format!("Usage: {} {}\n\nOptions:\n", command.name(), command.usage());
+    text.push_str(&help_row(" -help".to_string(), "Display this summary"));
+    let (parameters, options): (Vec<&OptionDef>, Vec<&OptionDef>) = command
+        .options()
+        .iter()
+        .partition(|option| option.value == ValueType::Parameter);
+    for option in options {
+        let start = match option.value.placeholder() {
+            Some(placeholder) => format!(" -{} {}", option.name, placeholder),
+            None => format!(" -{}", option.name),
+        };
+        text.push_str(&help_row(start, option.help));
+    }
+    if !parameters.is_empty() {
+        text.push_str("\nParameters:\n");
+        for parameter in parameters {
+            text.push_str(&help_row(format!(" {}", parameter.name), parameter.help));
+        }
+    }
+    text
+}
+
+fn help_row(start: String, help: &str) -> String {
+    if start.len() > 20 {
+        format!("{}\n{:21}{}\n", start, "", help)
+    } else {
+        format!("{:<20} {}\n", start, help)
+    }
+}
+
+/// The commands `ft_ssl` can run, looked up by name.
+pub struct Registry {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Registry {
+    pub fn empty() -> Self {
+        Registry { commands: Vec::new() }
+    }
+
+    /// Every command shipped with ft_ssl, plus one per cipher name that
+    /// runs `enc` with that cipher.
+    pub fn builtin() -> Self {
+        let mut registry = Registry::empty();
+        registry.register(Box::new(Enc));
+        registry.register(Box::new(Genpkey));
+        registry.register(Box::new(Genrsa));
+        registry.register(Box::new(Kdf));
+        registry.register(Box::new(Pkey));
+        registry.register(Box::new(Pkeyutl));
+        registry.register(Box::new(Prime));
+        registry.register(Box::new(Rand));
+        registry.register(Box::new(Rsa));
+        registry.register(Box::new(Rsautl));
+        for command in Cipher::NAMES.iter().filter_map(|name| CipherCommand::new(name)) {
+            registry.register(Box::new(command));
+        }
+        registry
+    }
+
+    /// Adds `command`, replacing any command registered under its name.
+    pub fn register(&mut self, command: Box<dyn Command>) {
+        self.commands.retain(|existing| existing.name() != command.name());
+        self.commands.push(command);
+    }
+
+    pub fn find(&self, name: &str) -> Option<&dyn Command> {
+        self.commands
+            .iter()
+            .find(|command| command.name() == name)
+            .map(|command| command.as_ref())
+    }
+
+    /// Runs `args[0]` with the remaining arguments. `help` lists the
+    /// commands, `help name` and `name -help` describe one.
+    pub fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        let name = match args.first() {
+            Some(name) => name.as_str(),
+            None => {
+                return Err(FtSslError::Usage(
+                    "usage: ft_ssl command [command opts] [command args]".to_string(),
+                ))
+            }
+        };
+        if name == "help" {
+            match args.get(1) {
+                Some(topic) => print!("{}", help_text(self.lookup(topic)?)),
+                None => print!("{}", self.listing()),
+            }
+            return Ok(());
+        }
+        let command = self.lookup(name)?;
+        let args = &args[1..];
+        if args.iter().take_while(|arg| *arg != "--").any(|arg| arg == "-help") {
+            print!("{}", help_text(command));
+            return Ok(());
+        }
+        command.run(args)
+    }
+
+    fn lookup(&self, name: &str) -> Result<&dyn Command, FtSslError> {
+        self.find(name).ok_or_else(|| {
+            FtSslError::Usage(format!(
+                "Error: '{}' is an invalid command.\n\n{}",
+                name,
+                self.listing().trim_end()
+            ))
+        })
+    }
+
+    /// The commands by section, one name per line.
+    pub fn listing(&self) -> String {
+        let sections = [
+            ("Standard commands", CommandKind::Standard),
+            ("Message Digest commands", CommandKind::MessageDigest),
+            ("Cipher commands", CommandKind::Cipher),
+        ];
+        let mut text = String::new();
+        for (title, kind) in sections {
+            let mut names: Vec<&str> = self
+                .commands
+                .iter()
+                .filter(|command| command.kind() == kind)
+                .map(|command| command.name())
+                .collect();
+            if kind == CommandKind::Standard {
+                names.push("help");
+            }
+            names.sort_unstable();
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(title);
+            text.push_str(":\n");
+            for name in names {
+                text.push_str(name);
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ECHO_OPTIONS: &[OptionDef] = &[
+        OptionDef::new("out", ValueType::OutFile, "Output file"),
+        OptionDef::flag("n", "Do not print the trailing newline"),
+        OptionDef::new("passphrase-source", ValueType::Pass, "Where the pass phrase comes from"),
+        OptionDef::parameter("word", "Word to print"),
+    ];
+
+    struct Echo;
+
+    impl Command for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn usage(&self) -> &str {
+            "[options] word"
+        }
+
+        fn options(&self) -> &[OptionDef] {
+            ECHO_OPTIONS
+        }
+
+        fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+            match args {
+                [word] if word == "fail" => Err(FtSslError::Operation("echo failed".to_string())),
+                _ => Ok(()),
+            }
+        }
+
+        fn kind(&self) -> CommandKind {
+            CommandKind::MessageDigest
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_help_text_layout() {
+        assert_eq!(
+            help_text(&Echo),
+            "Usage: echo [options] word\n\
+             \n\
+             Options:\n \
+             -help               Display this summary\n \
+             -out outfile        Output file\n \
+             -n                  Do not print the trailing newline\n \
+             -passphrase-source val\n                     \
+             Where the pass phrase comes from\n\
+             \n\
+             Parameters:\n \
+             word                Word to print\n"
+        );
+    }
+
+    #[test]
+    fn test_registered_commands_are_dispatched() {
+        let mut registry = Registry::builtin();
+        assert!(registry.find("echo").is_none());
+        registry.register(Box::new(Echo));
+        assert!(registry.run(&args(&["echo", "hello"])).is_ok());
+        assert!(matches!(registry.run(&args(&["echo", "fail"])), Err(FtSslError::Operation(_))));
+        assert!(registry.run(&args(&["echo", "-help", "fail"])).is_ok());
+        assert!(registry.run(&args(&["echo", "--", "-help"])).is_ok());
+        assert!(registry.run(&args(&["help", "echo"])).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_command_lists_every_section() {
+        let mut registry = Registry::builtin();
+        registry.register(Box::new(Echo));
+        let err = match registry.run(&args(&["foobar"])) {
+            Err(FtSslError::Usage(reason)) => reason,
+            _ => panic!("expected a usage error"),
+        };
+        assert!(err.starts_with("Error: 'foobar' is an invalid command.\n\nStandard commands:\n"));
+        assert!(err.contains("\ngenrsa\nhelp\nkdf\n"));
+        assert!(err.contains("\n\nMessage Digest commands:\necho\n\nCipher commands:\naes-128-cbc\n"));
+        assert!(err.ends_with("\ndes3"));
+        assert!(matches!(Registry::empty().run(&[]), Err(FtSslError::Usage(_))));
+    }
+
+    #[test]
+    fn test_every_builtin_has_help() {
+        let registry = Registry::builtin();
+        for command in &registry.commands {
+            let help = help_text(command.as_ref());
+            assert!(help.starts_with(&format!("Usage: {} ", command.name())));
+            assert!(registry.find(command.name()).is_some());
+        }
+        assert_eq!(registry.find("des3").map(|command| command.kind()), Some(CommandKind::Cipher));
+    }
+}
@@ -0,0 +1,46 @@
+use super::pkeyutl::{parse_rsautl_options, run};
+use crate::error::FtSslError;
+use super::registry::{Command, OptionDef, ValueType};
+
+pub const RSAUTL_OPTIONS: &[OptionDef] = &[
+    OptionDef::flag("encrypt", "Encrypt with public key"),
+    OptionDef::flag("decrypt", "Decrypt with private key"),
+    OptionDef::flag("sign", "Sign with private key"),
+    OptionDef::flag("verify", "Verify with public key, recovering the signed data"),
+    OptionDef::new("in", ValueType::InFile, "Input file"),
+    OptionDef::new("out", ValueType::OutFile, "Output file"),
+    OptionDef::new("inkey", ValueType::InFile, "Input key"),
+    OptionDef::new("keyform", ValueType::Format, "Key format (DER or PEM)"),
+    OptionDef::new("passin", ValueType::Pass, "Input file pass phrase source"),
+    OptionDef::flag("pubin", "Input is an RSA public key"),
+    OptionDef::flag("pkcs", "Use PKCS#1 v1.5 padding (default)"),
+    OptionDef::flag("oaep", "Use PKCS#1 OAEP"),
+    OptionDef::flag("raw", "Use no padding"),
+];
+
+pub struct Rsautl;
+
+impl Command for Rsautl {
+    fn name(&self) -> &str {
+        "rsautl"
+    }
+
+    fn usage(&self) -> &str {
+        "[options]"
+    }
+
+    fn options(&self) -> &[OptionDef] {
+        RSAUTL_OPTIONS
+    }
+
+    fn run(&self, args: &[String]) -> Result<(), FtSslError> {
+        rsautl_command(args)
+    }
+}
+
+/// The deprecated `rsautl`, kept for old scripts. Same operations and
+/// padding code as `pkeyutl`.
+pub fn rsautl_command(args: &[String]) -> Result<(), FtSslError> {
+    let option = parse_rsautl_options(args)?;
+    run(&option, "rsautl")
+}
@@ -0,0 +1,236 @@
+use crate::error::FtSslError;
+use num::bigint::Sign;
+use num::BigInt;
+
+// Minimal DER (X.690) support: just the universal types needed for keys.
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+pub fn encode_length(length: usize) -> Vec<u8> {
+    if length < 0x80 {
+        return vec![length as u8];
+    }
+    let bytes: Vec<u8> = length
+        .to_be_bytes()
+        .iter()
+        .skip_while(|&&b| b == 0)
+        .copied()
+        .collect();
+    let mut encoded = vec![0x80 | bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+pub fn encode_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    encoded.extend(encode_length(content.len()));
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+/// Two's complement, minimal length, as DER requires.
+pub fn encode_integer(value: &BigInt) -> Vec<u8> {
+    encode_tlv(TAG_INTEGER, &value.to_signed_bytes_be())
+}
+
+pub fn encode_sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    encode_tlv(TAG_SEQUENCE, &items.concat())
+}
+
+pub fn encode_octet_string(content: &[u8]) -> Vec<u8> {
+    encode_tlv(TAG_OCTET_STRING, content)
+}
+
+/// A BIT STRING with no unused bits.
+pub fn encode_bit_string(content: &[u8]) -> Vec<u8> {
+    let mut bits = vec![0];
+    bits.extend_from_slice(content);
+    encode_tlv(TAG_BIT_STRING, &bits)
+}
+
+pub fn encode_null() -> Vec<u8> {
+    vec![TAG_NULL, 0]
+}
+
+/// Encodes a dotted object identifier such as `1.2.840.113549.1.1.1`.
+pub fn encode_oid(oid: &[u64]) -> Vec<u8> {
+    let mut content = Vec::new();
+    let mut arcs = vec![oid[0] * 40 + oid[1]];
+    arcs.extend_from_slice(&oid[2..]);
+
+    for arc in arcs {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        chunk.reverse();
+        content.extend(chunk);
+    }
+    encode_tlv(TAG_OID, &content)
+}
+
+/// Sequential reader over the TLVs of one DER level.
+pub struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        DerReader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Reads the next element, which must carry `tag`, and returns its content.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], FtSslError> {
+        let (found, content) = self.read_any()?;
+        if found != tag {
+            return Err(FtSslError::Asn1Parse(format!(
+                "unexpected tag 0x{:02x}, expected 0x{:02x}",
+                found, tag
+            )));
+        }
+        Ok(content)
+    }
+
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8]), FtSslError> {
+        if self.data.len() < 2 {
+            return Err(FtSslError::Asn1Parse("truncated DER element".to_string()));
+        }
+        let tag = self.data[0];
+        let first = self.data[1] as usize;
+        let (length, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 || self.data.len() < 2 + count {
+                return Err(FtSslError::Asn1Parse("invalid DER length".to_string()));
+            }
+            let length = self.data[2..2 + count]
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            (length, 2 + count)
+        };
+        if self.data.len() < header + length {
+            return Err(FtSslError::Asn1Parse("truncated DER element".to_string()));
+        }
+        let content = &self.data[header..header + length];
+        self.data = &self.data[header + length..];
+        Ok((tag, content))
+    }
+
+    pub fn read_sequence(&mut self) -> Result<DerReader<'a>, FtSslError> {
+        self.read(TAG_SEQUENCE).map(DerReader::new)
+    }
+
+    pub fn read_integer(&mut self) -> Result<BigInt, FtSslError> {
+        let content = self.read(TAG_INTEGER)?;
+        if content.is_empty() {
+            return Err(FtSslError::Asn1Parse("empty INTEGER".to_string()));
+        }
+        Ok(BigInt::from_signed_bytes_be(content))
+    }
+
+    /// Reads a non-negative INTEGER that must fit in a `u64`.
+    pub fn read_small_integer(&mut self) -> Result<u64, FtSslError> {
+        let value = self.read_integer()?;
+        let (sign, bytes) = value.to_bytes_be();
+        if sign == Sign::Minus || bytes.len() > 8 {
+            return Err(FtSslError::Asn1Parse("INTEGER out of range".to_string()));
+        }
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    pub fn read_octet_string(&mut self) -> Result<&'a [u8], FtSslError> {
+        self.read(TAG_OCTET_STRING)
+    }
+
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], FtSslError> {
+        let content = self.read(TAG_BIT_STRING)?;
+        match content.split_first() {
+            Some((0, bits)) => Ok(bits),
+            _ => Err(FtSslError::Asn1Parse("unsupported BIT STRING".to_string())),
+        }
+    }
+
+    pub fn read_oid(&mut self) -> Result<Vec<u64>, FtSslError> {
+        let content = self.read(TAG_OID)?;
+        let mut arcs = Vec::new();
+        let mut value = 0u64;
+
+        for &byte in content {
+            value = (value << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                if arcs.is_empty() {
+                    let first = (value / 40).min(2);
+                    arcs.push(first);
+                    arcs.push(value - first * 40);
+                } else {
+                    arcs.push(value);
+                }
+                value = 0;
+            }
+        }
+        if arcs.is_empty() {
+            return Err(FtSslError::Asn1Parse("empty OBJECT IDENTIFIER".to_string()));
+        }
+        Ok(arcs)
+    }
+
+    pub fn read_null(&mut self) -> Result<(), FtSslError> {
+        self.read(TAG_NULL).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_length() {
+        assert_eq!(encode_length(0x7f), vec![0x7f]);
+        assert_eq!(encode_length(0x80), vec![0x81, 0x80]);
+        assert_eq!(encode_length(0x0122), vec![0x82, 0x01, 0x22]);
+    }
+
+    #[test]
+    fn test_integer_round_trip() {
+        for value in [0i64, 127, 128, 256, 65537, -1, -129] {
+            let encoded = encode_integer(&BigInt::from(value));
+            let decoded = DerReader::new(&encoded).read_integer().unwrap();
+            assert_eq!(decoded, BigInt::from(value));
+        }
+        assert_eq!(encode_integer(&BigInt::from(128)), vec![0x02, 0x02, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_oid_round_trip() {
+        let rsa_encryption = [1, 2, 840, 113549, 1, 1, 1];
+        let encoded = encode_oid(&rsa_encryption);
+
+        assert_eq!(
+            encoded,
+            vec![0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01]
+        );
+        assert_eq!(DerReader::new(&encoded).read_oid().unwrap(), rsa_encryption);
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        assert!(DerReader::new(&[0x30, 0x05, 0x02]).read_sequence().is_err());
+        assert!(DerReader::new(&[0x30]).read_sequence().is_err());
+    }
+}
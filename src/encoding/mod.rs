@@ -0,0 +1,5 @@
+pub mod der;
+pub mod pem;
+pub mod pkcs1;
+pub mod pkcs8;
+pub mod spki;
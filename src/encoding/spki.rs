@@ -0,0 +1,40 @@
+use super::der::{encode_bit_string, encode_null, encode_oid, encode_sequence, DerReader};
+use super::pkcs1::{decode_rsa_public_key, encode_rsa_public_key};
+use crate::algorithms::rsa::RsaPublicKey;
+use crate::error::FtSslError;
+
+// X.509 SubjectPublicKeyInfo, the `PUBLIC KEY` PEM label.
+
+pub const RSA_ENCRYPTION: [u64; 7] = [1, 2, 840, 113549, 1, 1, 1];
+
+/// AlgorithmIdentifier { rsaEncryption, NULL }.
+pub fn rsa_algorithm_identifier() -> Vec<u8> {
+    encode_sequence(&[encode_oid(&RSA_ENCRYPTION), encode_null()])
+}
+
+/// Reads an AlgorithmIdentifier and checks it names rsaEncryption.
+pub fn read_rsa_algorithm_identifier(reader: &mut DerReader) -> Result<(), FtSslError> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read_oid()? != RSA_ENCRYPTION {
+        return Err(FtSslError::Asn1Parse("unsupported public key algorithm".to_string()));
+    }
+    if !algorithm.is_empty() {
+        algorithm.read_null()?;
+    }
+    Ok(())
+}
+
+pub fn encode_public_key_info(key: &RsaPublicKey) -> Vec<u8> {
+    encode_sequence(&[
+        rsa_algorithm_identifier(),
+        encode_bit_string(&encode_rsa_public_key(key)),
+    ])
+}
+
+pub fn decode_public_key_info(der: &[u8]) -> Result<RsaPublicKey, FtSslError> {
+    let mut outer = DerReader::new(der);
+    let mut seq = outer.read_sequence()?;
+
+    read_rsa_algorithm_identifier(&mut seq)?;
+    decode_rsa_public_key(seq.read_bit_string()?)
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::io;
+
+/// Every failure the library reports. Commands return it instead of
+/// exiting, so that bad input never takes down a process embedding the
+/// library; the binary turns it into OpenSSL's messages and exit status.
+#[derive(Debug)]
+pub enum FtSslError {
+    /// A file or standard stream could not be opened, read or written.
+    Io(String),
+    /// Malformed or unsupported DER.
+    Asn1Parse(String),
+    /// Malformed PEM armour or headers.
+    Pem(String),
+    /// A pass phrase that could not be read, or that fails to decrypt.
+    BadPassword(String),
+    /// A key that fails its consistency checks or cannot be used.
+    InvalidKey(String),
+    /// An unknown option, a missing value or an invalid argument.
+    Usage(String),
+    /// A cryptographic operation that failed on otherwise valid input.
+    Operation(String),
+    /// A signature that does not verify; reported on standard output.
+    VerificationFailure,
+}
+
+impl FtSslError {
+    /// The same error with `context` in front of its message, keeping the
+    /// kind intact.
+    pub fn context(self, context: &str) -> FtSslError {
+        let wrap = |reason: String| format!("{}: {}", context, reason);
+        match self {
+            FtSslError::Io(reason) => FtSslError::Io(wrap(reason)),
+            FtSslError::Asn1Parse(reason) => FtSslError::Asn1Parse(wrap(reason)),
+            FtSslError::Pem(reason) => FtSslError::Pem(wrap(reason)),
+            FtSslError::BadPassword(reason) => FtSslError::BadPassword(wrap(reason)),
+            FtSslError::InvalidKey(reason) => FtSslError::InvalidKey(wrap(reason)),
+            FtSslError::Usage(reason) => FtSslError::Usage(wrap(reason)),
+            FtSslError::Operation(reason) => FtSslError::Operation(wrap(reason)),
+            FtSslError::VerificationFailure => FtSslError::VerificationFailure,
+        }
+    }
+}
+
+impl fmt::Display for FtSslError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FtSslError::Io(reason)
+            | FtSslError::Asn1Parse(reason)
+            | FtSslError::Pem(reason)
+            | FtSslError::BadPassword(reason)
+            | FtSslError::InvalidKey(reason)
+            | FtSslError::Usage(reason)
+            | FtSslError::Operation(reason) => write!(f, "{}", reason),
+            FtSslError::VerificationFailure => write!(f, "Signature Verification Failure"),
+        }
+    }
+}
+
+impl std::error::Error for FtSslError {}
+
+impl From<io::Error> for FtSslError {
+    fn from(err: io::Error) -> Self {
+        FtSslError::Io(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_context_keeps_the_kind() {
+        let err = FtSslError::Asn1Parse("truncated DER element".to_string()).context("unable to load Private Key");
+        assert!(matches!(err, FtSslError::Asn1Parse(_)));
+        assert_eq!(err.to_string(), "unable to load Private Key: truncated DER element");
+        assert!(matches!(
+            FtSslError::VerificationFailure.context("ignored"),
+            FtSslError::VerificationFailure
+        ));
+    }
+
+    #[test]
+    fn test_io_errors_convert() {
+        let err: FtSslError = io::Error::new(io::ErrorKind::NotFound, "no such file").into();
+        assert!(matches!(err, FtSslError::Io(_)));
+        assert_eq!(err.to_string(), "no such file");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod algorithms;
 pub mod commands;
+pub mod encoding;
+pub mod error;
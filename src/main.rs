@@ -1,28 +1,17 @@
-pub mod algorithms;
-pub mod commands;
+use ft_ssl::commands::registry::Registry;
+use ft_ssl::error::FtSslError;
 use std::env;
-use crate::commands::rsa::rsa_command;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() < 2 {
-        panic!("usage: ft_ssl command [command opts] [command args]");
+    if let Err(err) = Registry::builtin().run(&args) {
+        // OpenSSL reports a failed verification on standard output and
+        // exits with 1 for every kind of failure.
+        match err {
+            FtSslError::VerificationFailure => println!("{}", err),
+            err => eprintln!("{}", err),
+        }
+        std::process::exit(1);
     }
-    let command = &args[1];
-
-    match command.as_str() {
-        "genrsa" => genrsa_command(&args[2..]),
-        "rsa" => {rsa_command(&args[2..]);},
-        "rsault" => rsault_command(&args[2..]),
-        _ => panic!("Error: '{}' is an invalid command.", command),
-    }
-}
-
-fn genrsa_command(command: &[String]) {
-    println!("command: {:?}", command)
-}
-
-fn rsault_command(command: &[String]) {
-    println!("command: {:?}", command)
 }